@@ -0,0 +1,190 @@
+use crate::collections::{GameState, MonteCarloTree, TreePolicy};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Limits on how long `MonteCarloTree::search` may run. The search stops as soon as any limit
+/// is reached; a budget with no limits runs until it is cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct SearchBudget {
+    pub iterations: Option<usize>,
+    pub duration: Option<Duration>,
+    pub max_nodes: Option<usize>,
+    pub cancel: Option<CancellationToken>,
+}
+
+impl SearchBudget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.iterations = Some(iterations);
+        self
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Stop once the tree holds this many nodes. The limit is checked before each iteration, so
+    /// the final expansion may overshoot it by up to one node's worth of children.
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    pub fn cancel_on(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+}
+
+/// A flag shared between a running search and whoever wants to stop it early. Clones refer to
+/// the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Iterations,
+    Duration,
+    MaxNodes,
+    Cancelled,
+    /// The tree could not be searched any further.
+    Exhausted,
+    /// The solver proved the outcome at the root.
+    Solved,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchSummary {
+    pub iterations: usize,
+    /// Nodes added to the tree during the search.
+    pub nodes: usize,
+    pub elapsed: Duration,
+    pub stop: StopReason,
+}
+
+impl<G: GameState> MonteCarloTree<G> {
+    /// Runs iterations from the root until `budget` is spent.
+    pub fn search<P: TreePolicy<G>>(&mut self, policy: &P, budget: &SearchBudget) -> SearchSummary {
+        let start = Instant::now();
+        let initial_nodes = self.graph.node_count();
+        let mut iterations = 0;
+
+        let stop = loop {
+            if let Some(reason) = self.budget_spent(budget, iterations, start) {
+                break reason;
+            }
+            if self.iterate_with(policy).is_none() {
+                break StopReason::Exhausted;
+            }
+            iterations += 1;
+        };
+
+        SearchSummary {
+            iterations,
+            nodes: self.graph.node_count().saturating_sub(initial_nodes),
+            elapsed: start.elapsed(),
+            stop,
+        }
+    }
+
+    pub(crate) fn budget_spent(
+        &self,
+        budget: &SearchBudget,
+        iterations: usize,
+        start: Instant,
+    ) -> Option<StopReason> {
+        if budget
+            .cancel
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            Some(StopReason::Cancelled)
+        } else if self.is_solved() {
+            Some(StopReason::Solved)
+        } else if budget.iterations.is_some_and(|n| iterations >= n) {
+            Some(StopReason::Iterations)
+        } else if budget
+            .max_nodes
+            .is_some_and(|n| self.graph.node_count() >= n)
+        {
+            Some(StopReason::MaxNodes)
+        } else if budget.duration.is_some_and(|d| start.elapsed() >= d) {
+            Some(StopReason::Duration)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::{testing::Race, Ucb1};
+
+    #[test]
+    fn iteration_budget() {
+        let mut tree = MonteCarloTree::<Race>::seed_from_u64(0);
+        let summary = tree.search(&Ucb1::default(), &SearchBudget::new().iterations(250));
+        assert_eq!(summary.iterations, 250);
+        assert_eq!(summary.stop, StopReason::Iterations);
+        assert_eq!(summary.nodes, tree.graph.node_count() - 1);
+    }
+
+    #[test]
+    fn node_budget() {
+        let mut tree = MonteCarloTree::<Race>::seed_from_u64(0);
+        let budget = SearchBudget::new().max_nodes(20).iterations(100_000);
+        let summary = tree.search(&Ucb1::default(), &budget);
+        assert_eq!(summary.stop, StopReason::MaxNodes);
+        assert!((20..=21).contains(&tree.graph.node_count()));
+    }
+
+    #[test]
+    fn duration_budget() {
+        let mut tree = MonteCarloTree::<Race>::seed_from_u64(0);
+        let budget = SearchBudget::new().duration(Duration::from_millis(20));
+        let summary = tree.search(&Ucb1::default(), &budget);
+        assert_eq!(summary.stop, StopReason::Duration);
+        assert!(summary.elapsed >= Duration::from_millis(20));
+        assert!(summary.iterations > 0);
+    }
+
+    #[test]
+    fn cancelled_budget() {
+        let token = CancellationToken::new();
+        let budget = SearchBudget::new().cancel_on(token.clone());
+
+        let handle = std::thread::spawn(move || {
+            let mut tree = MonteCarloTree::<Race>::seed_from_u64(0);
+            tree.search(&Ucb1::default(), &budget)
+        });
+        std::thread::sleep(Duration::from_millis(20));
+        token.cancel();
+
+        let summary = handle.join().unwrap();
+        assert_eq!(summary.stop, StopReason::Cancelled);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::collections::{GameState, MctsNode, MonteCarloTree};
+use nanorand::{Rng, WyRand};
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction::Outgoing};
+
+impl<G: GameState> MonteCarloTree<G> {
+    /// The outcome to follow out of a chance node: whichever child is furthest behind its share
+    /// of the visits, so that over many iterations each outcome is searched in proportion to its
+    /// probability and the chance node's statistics approach the expected value.
+    pub(crate) fn select_outcome(&self, node: NodeIndex, path: &[NodeIndex]) -> Option<NodeIndex> {
+        let visits = self.graph.node_weight(node)?.visits() as f64;
+        self.graph
+            .edges_directed(node, Outgoing)
+            .filter(|edge| !path.contains(&edge.target()))
+            .map(|edge| {
+                let share = edge.weight().probability.unwrap_or_default() * (visits + 1.0);
+                (edge.target(), share - edge.weight().visits as f64)
+            })
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(ix, _)| ix)
+    }
+}
+
+/// Draws one of `outcomes` according to their probabilities, which should sum to one.
+pub(crate) fn sample_outcome<G: GameState>(
+    outcomes: Vec<(G::Player, G::Action, f64)>,
+    rng: &mut WyRand,
+) -> Option<(G::Player, G::Action)> {
+    let mut remaining = rng.generate::<f64>();
+    let mut last = None;
+    for (player, action, probability) in outcomes {
+        if remaining < probability {
+            return Some((player, action));
+        }
+        remaining -= probability;
+        last = Some((player, action));
+    }
+    // rounding can leave a sliver of probability unassigned
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collections::{
+        testing::Gamble, GameState, MctsNode, MonteCarloTree, SearchBudget, Ucb1,
+    };
+    use nanorand::WyRand;
+    use petgraph::Direction::Outgoing;
+
+    fn decide(percent: u8) -> MonteCarloTree<Gamble> {
+        let mut tree = MonteCarloTree::seed_from_state(0, Gamble::new(percent));
+        tree.search(&Ucb1::default(), &SearchBudget::new().iterations(5000));
+        tree
+    }
+
+    #[test]
+    fn expands_chance_outcomes() {
+        let state = Gamble::new(70).step(&0, &Gamble::RISKY).unwrap();
+        assert!(state.is_chance());
+
+        let mut tree = MonteCarloTree::seed_from_state(0, state);
+        tree.expand().unwrap();
+        let mut probabilities = tree
+            .graph
+            .edges_directed(tree.root, Outgoing)
+            .map(|edge| edge.weight().probability.unwrap())
+            .collect::<Vec<_>>();
+        probabilities.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(probabilities.len(), 2);
+        assert!((probabilities[0] - 0.3).abs() < 1e-9);
+        assert!((probabilities[1] - 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn chance_visits_follow_probabilities() {
+        let state = Gamble::new(70).step(&0, &Gamble::RISKY).unwrap();
+        let mut tree = MonteCarloTree::seed_from_state(0, state);
+        for _ in 0..1001 {
+            tree.iterate(1.0).unwrap();
+        }
+        for edge in tree.graph.edges_directed(tree.root, Outgoing) {
+            let expected = edge.weight().probability.unwrap() * 1000.0;
+            assert!((edge.weight().visits as f64 - expected).abs() <= 2.0);
+        }
+
+        let mean = tree.graph[tree.root].mean_reward(&0);
+        assert!((mean - 0.7).abs() < 0.01);
+    }
+
+    #[test]
+    fn chooses_by_expected_value() {
+        let safe = decide(40).best_action(Default::default()).unwrap();
+        assert_eq!(safe.action, Gamble::SAFE);
+
+        let risky = decide(60).best_action(Default::default()).unwrap();
+        assert_eq!(risky.action, Gamble::RISKY);
+    }
+
+    #[test]
+    fn rollouts_sample_outcomes() {
+        let state = Gamble::new(25).step(&0, &Gamble::RISKY).unwrap();
+        let node = crate::collections::MonteCarloNode::from_state(state);
+        let mut rng = WyRand::new_seed(0);
+        let wins = (0..10_000)
+            .filter(|_| node.rollout(&mut rng).get(&0) == Some(&1.0))
+            .count();
+        assert!((2300..2700).contains(&wins));
+    }
+}
@@ -1,5 +1,20 @@
+mod budget;
+mod chance;
+mod parallel;
+mod policy;
+mod reroot;
+mod selection;
+mod solver;
+mod transposition;
 mod tree;
 
-pub use tree::{GameState, MonteCarloTree};
+#[cfg(test)]
+mod testing;
+
+pub use budget::{CancellationToken, SearchBudget, SearchSummary, StopReason};
+pub use policy::{Puct, ThompsonSampling, TreePolicy, Ucb1, Ucb1Tuned};
+pub use selection::{BestAction, ChildStats, MoveSelection};
+pub use solver::{GameResult, Proof};
+pub use tree::{GameState, MctsNode, MonteCarloEdge, MonteCarloNode, MonteCarloTree, RewardMap};
 
 // pub use tree::Tree;
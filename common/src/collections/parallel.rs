@@ -0,0 +1,286 @@
+use crate::collections::{
+    budget::StopReason, GameState, MctsNode, MonteCarloNode, MonteCarloTree, SearchBudget,
+    SearchSummary, TreePolicy,
+};
+use nanorand::WyRand;
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction::Outgoing};
+use std::{sync::mpsc, time::Instant};
+
+/// Visits charged to each node on a path whose rollout is still running, so that other picks in
+/// the same batch are steered elsewhere.
+const VIRTUAL_LOSS: u32 = 1;
+
+impl<G> MonteCarloTree<G>
+where
+    G: GameState + Send,
+    G::Player: Send,
+    G::Action: Send,
+    G::Reward: Send,
+{
+    /// Root parallelism: searches this tree on the current thread and `threads - 1` fresh trees
+    /// from the same root state on worker threads, then adds the workers' root and root-child
+    /// statistics into this tree.
+    ///
+    /// Worker seeds are drawn from this tree's generator, so with an iteration budget the result
+    /// depends only on the seed and the thread count.
+    pub fn search_root_parallel<P>(
+        &mut self,
+        policy: &P,
+        budget: &SearchBudget,
+        threads: usize,
+    ) -> SearchSummary
+    where
+        P: TreePolicy<G> + Clone + Send,
+    {
+        let seeds = (1..threads).map(|_| self.next_seed()).collect::<Vec<_>>();
+        let trees = seeds
+            .into_iter()
+            .map(|seed| self.fresh(seed, self.root_state().clone()))
+            .collect::<Vec<_>>();
+
+        let (summary, workers) = std::thread::scope(|scope| {
+            let handles = trees
+                .into_iter()
+                .map(|mut tree| {
+                    let policy = policy.clone();
+                    scope.spawn(move || {
+                        let summary = tree.search(&policy, budget);
+                        (tree, summary)
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let summary = self.search(policy, budget);
+            let workers = handles
+                .into_iter()
+                .map(|handle| handle.join().expect("search thread to finish"))
+                .collect::<Vec<_>>();
+            (summary, workers)
+        });
+
+        workers.into_iter().fold(summary, |total, (tree, summary)| {
+            self.merge_root(&tree);
+            SearchSummary {
+                iterations: total.iterations + summary.iterations,
+                nodes: total.nodes + summary.nodes,
+                elapsed: total.elapsed.max(summary.elapsed),
+                stop: total.stop,
+            }
+        })
+    }
+
+    /// Tree parallelism: a single shared tree, searched in batches of up to `threads` leaves.
+    /// Leaves are selected one after another with a virtual loss charged along each path, rolled
+    /// out simultaneously on worker threads, then backpropagated in selection order. Leaves that
+    /// already have visits are expanded and one of their new children is rolled out instead.
+    ///
+    /// Selection and backpropagation never race, so with an iteration budget the result depends
+    /// only on the seed and the thread count.
+    pub fn search_tree_parallel<P: TreePolicy<G>>(
+        &mut self,
+        policy: &P,
+        budget: &SearchBudget,
+        threads: usize,
+    ) -> SearchSummary {
+        let start = Instant::now();
+        let initial_nodes = self.graph.node_count();
+        let mut iterations = 0;
+        let seeds = (0..threads.max(1))
+            .map(|_| self.next_seed())
+            .collect::<Vec<_>>();
+
+        let stop = std::thread::scope(|scope| {
+            let workers = seeds
+                .into_iter()
+                .map(|seed| {
+                    let (jobs, job_rx) = mpsc::channel::<G>();
+                    let (result_tx, results) = mpsc::channel();
+                    scope.spawn(move || {
+                        let mut rng = WyRand::new_seed(seed);
+                        for state in job_rx {
+                            let reward = MonteCarloNode::from_state(state).rollout(&mut rng);
+                            if result_tx.send(reward).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                    (jobs, results)
+                })
+                .collect::<Vec<_>>();
+
+            loop {
+                if let Some(reason) = self.budget_spent(budget, iterations, start) {
+                    break reason;
+                }
+
+                let batch = budget
+                    .iterations
+                    .map_or(workers.len(), |n| n - iterations)
+                    .min(workers.len());
+                let mut paths = Vec::with_capacity(batch);
+                for _ in 0..batch {
+                    let Some(path) = self.select_for_rollout(policy) else {
+                        break;
+                    };
+                    self.charge_virtual_loss(&path, true);
+                    paths.push(path);
+                }
+                if paths.is_empty() {
+                    break StopReason::Exhausted;
+                }
+
+                for (path, (jobs, _)) in paths.iter().zip(workers.iter()) {
+                    let leaf = self.graph[*path.last().expect("paths to be non-empty")].state();
+                    jobs.send(leaf.clone())
+                        .expect("rollout thread to be running");
+                }
+
+                for (path, (_, results)) in paths.iter().zip(workers.iter()) {
+                    let reward = results.recv().expect("rollout thread to reply");
+                    self.charge_virtual_loss(path, false);
+                    let _ = self.backpropagate_path(&reward, path);
+                    iterations += 1;
+                }
+            }
+        });
+
+        SearchSummary {
+            iterations,
+            nodes: self.graph.node_count().saturating_sub(initial_nodes),
+            elapsed: start.elapsed(),
+            stop,
+        }
+    }
+}
+
+impl<G: GameState> MonteCarloTree<G> {
+    /// Selects a leaf, expanding it and stepping into a new child if it has been visited before.
+    fn select_for_rollout<P: TreePolicy<G>>(&mut self, policy: &P) -> Option<Vec<NodeIndex>> {
+        let mut path = self.select_path(self.root, policy)?;
+        let leaf = *path.last()?;
+
+        if self.graph.node_weight(leaf)?.visits() > 0 {
+            self.expand_from(leaf)?;
+            let child = self.select_from(leaf, policy)?;
+            if child != leaf {
+                path.push(child);
+            }
+        }
+        Some(path)
+    }
+
+    fn charge_virtual_loss(&mut self, path: &[NodeIndex], charge: bool) {
+        for &ix in path {
+            let visits = self.graph[ix].visits_mut();
+            if charge {
+                *visits += VIRTUAL_LOSS;
+            } else {
+                *visits -= VIRTUAL_LOSS;
+            }
+        }
+    }
+
+    /// Adds the root and root-child statistics of `other`, which must share this tree's root
+    /// state, into this tree. Children are matched up by action.
+    fn merge_root(&mut self, other: &Self) {
+        let has_children = |tree: &Self| {
+            tree.graph
+                .neighbors_directed(tree.root, Outgoing)
+                .next()
+                .is_some()
+        };
+        if !has_children(self) && has_children(other) {
+            let _ = self.expand_from(self.root);
+        }
+
+        let root = self.root;
+        absorb(&mut self.graph[root], &other.graph[other.root]);
+
+        for edge in other.graph.edges_directed(other.root, Outgoing) {
+            let Some(mine) = self.find_edge(self.root, &edge.weight().action) else {
+                continue;
+            };
+            self.graph[mine].visits += edge.weight().visits;
+            let (_, child) = self
+                .graph
+                .edge_endpoints(mine)
+                .expect("edge to be in the tree");
+            absorb(&mut self.graph[child], &other.graph[edge.target()]);
+        }
+    }
+}
+
+fn absorb<G: GameState>(node: &mut MonteCarloNode<G>, other: &MonteCarloNode<G>) {
+    *node.visits_mut() += other.visits();
+    for (player, reward) in other.reward() {
+        let entry = node.reward_mut().entry(player.clone()).or_default();
+        *entry = *entry + *reward;
+    }
+    for (player, squared) in other.squared_reward() {
+        *node.squared_reward_mut().entry(player.clone()).or_default() += squared;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::{testing::Race, ChildStats, Ucb1};
+
+    fn visits(stats: Vec<ChildStats<Race>>) -> Vec<(u8, u32, f64)> {
+        stats
+            .into_iter()
+            .map(|c| (c.action, c.visits, c.mean_reward))
+            .collect()
+    }
+
+    #[test]
+    fn root_parallel_is_deterministic() {
+        let budget = SearchBudget::new().iterations(300);
+        let run = || {
+            let mut tree = MonteCarloTree::<Race>::seed_from_state(3, Race::new(15));
+            let summary = tree.search_root_parallel(&Ucb1::default(), &budget, 4);
+            (tree, summary)
+        };
+
+        let (a, summary) = run();
+        let (b, _) = run();
+        assert_eq!(summary.iterations, 1200);
+        assert_eq!(visits(a.root_stats()), visits(b.root_stats()));
+
+        // every rollout but the first one in each tree passes through a root child
+        let child_visits = a.root_stats().iter().map(|c| c.visits).sum::<u32>();
+        assert_eq!(a.graph[a.root].visits(), child_visits + 4);
+    }
+
+    #[test]
+    fn tree_parallel_is_deterministic() {
+        let budget = SearchBudget::new().iterations(500);
+        let run = || {
+            let mut tree = MonteCarloTree::<Race>::seed_from_state(3, Race::new(15));
+            let summary = tree.search_tree_parallel(&Ucb1::default(), &budget, 4);
+            (tree, summary)
+        };
+
+        let (a, summary) = run();
+        let (b, _) = run();
+        assert_eq!(summary.iterations, 500);
+        assert_eq!(a.graph[a.root].visits(), 500);
+        assert_eq!(a.graph.node_count(), b.graph.node_count());
+        assert_eq!(visits(a.root_stats()), visits(b.root_stats()));
+
+        // virtual losses are all paid back
+        for edge in a.graph.edge_references() {
+            assert_eq!(edge.weight().visits, a.graph[edge.target()].visits());
+        }
+    }
+
+    #[test]
+    fn parallel_search_finds_winning_move() {
+        // 14 away from the target, so taking two leaves the opponent on a multiple of three
+        let budget = SearchBudget::new().iterations(4000);
+        let mut tree = MonteCarloTree::<Race>::seed_from_state(0, Race::new(14));
+        tree.search_tree_parallel(&Ucb1::default(), &budget, 4);
+        let best = tree.best_action(Default::default()).unwrap();
+        assert_eq!(best.action, 2);
+    }
+}
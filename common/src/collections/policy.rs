@@ -0,0 +1,239 @@
+use crate::{
+    collections::{GameState, MctsNode, MonteCarloNode},
+    traits::Seeded,
+};
+use nanorand::{Rng, WyRand};
+use std::cell::RefCell;
+
+/// Strategy used to pick which child to descend into while selecting a leaf.
+///
+/// Children are scored from the point of view of the player to move at `parent`, and the child
+/// with the highest score is chosen.
+pub trait TreePolicy<G: GameState> {
+    fn score(&self, parent: &MonteCarloNode<G>, child: &MonteCarloNode<G>) -> f64;
+}
+
+/// The classic UCB1 formula: `mean + c * sqrt(ln(N) / n)`.
+#[derive(Debug, Clone, Copy)]
+pub struct Ucb1 {
+    pub c: f64,
+}
+
+impl Ucb1 {
+    pub fn new(c: f64) -> Self {
+        Self { c }
+    }
+}
+
+impl Default for Ucb1 {
+    fn default() -> Self {
+        Self::new(std::f64::consts::SQRT_2)
+    }
+}
+
+impl<G: GameState> TreePolicy<G> for Ucb1 {
+    fn score(&self, parent: &MonteCarloNode<G>, child: &MonteCarloNode<G>) -> f64 {
+        let visits = child.visits() as f64;
+        if visits == 0.0 {
+            return f64::INFINITY;
+        }
+
+        let parent_visits = parent.visits() as f64;
+        let mean = child.mean_reward(&parent.state().current_player());
+        mean + self.c * (parent_visits.ln() / visits).sqrt()
+    }
+}
+
+/// UCB1-Tuned, which replaces the exploration constant with an upper bound on the variance of
+/// the child's rewards. Assumes rewards fall in `[0, 1]`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Ucb1Tuned;
+
+impl Ucb1Tuned {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<G: GameState> TreePolicy<G> for Ucb1Tuned {
+    fn score(&self, parent: &MonteCarloNode<G>, child: &MonteCarloNode<G>) -> f64 {
+        let visits = child.visits() as f64;
+        if visits == 0.0 {
+            return f64::INFINITY;
+        }
+
+        let player = parent.state().current_player();
+        let log_parent = (parent.visits() as f64).ln();
+        let mean = child.mean_reward(&player);
+        let squared = child
+            .squared_reward()
+            .get(&player)
+            .copied()
+            .unwrap_or_default();
+        let variance = squared / visits - mean * mean + (2.0 * log_parent / visits).sqrt();
+        mean + (log_parent / visits * variance.min(0.25)).sqrt()
+    }
+}
+
+/// PUCT as used by AlphaZero: `mean + c * P * sqrt(N) / (1 + n)`, where the prior `P` is
+/// computed from the parent and child states. Priors do not need to be normalized.
+#[derive(Debug, Clone, Copy)]
+pub struct Puct<F> {
+    pub c: f64,
+    prior: F,
+}
+
+impl<F> Puct<F> {
+    pub fn new(c: f64, prior: F) -> Self {
+        Self { c, prior }
+    }
+}
+
+impl<G: GameState> Puct<fn(&G, &G) -> f64> {
+    /// PUCT with every child given the same prior.
+    pub fn uniform(c: f64) -> Self {
+        Self::new(c, |_, _| 1.0)
+    }
+}
+
+impl<G, F> TreePolicy<G> for Puct<F>
+where
+    G: GameState,
+    F: Fn(&G, &G) -> f64,
+{
+    fn score(&self, parent: &MonteCarloNode<G>, child: &MonteCarloNode<G>) -> f64 {
+        let prior = (self.prior)(parent.state(), child.state());
+        let visits = child.visits() as f64;
+        let parent_visits = parent.visits() as f64;
+        let mean = if visits == 0.0 {
+            0.0
+        } else {
+            child.mean_reward(&parent.state().current_player())
+        };
+        mean + self.c * prior * parent_visits.sqrt() / (1.0 + visits)
+    }
+}
+
+/// Thompson sampling with a `Beta(1 + w, 1 + n - w)` posterior over each child's win rate, where
+/// `w` is the total reward and `n` the visit count. Assumes rewards fall in `[0, 1]`.
+///
+/// Every clone draws from its own stream, seeded from the original's, so policies cloned for
+/// parallel workers don't all make the same draws.
+#[derive(Debug)]
+pub struct ThompsonSampling {
+    rng: RefCell<WyRand>,
+}
+
+impl ThompsonSampling {
+    pub fn new() -> Self {
+        Self::random()
+    }
+}
+
+impl Default for ThompsonSampling {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for ThompsonSampling {
+    fn clone(&self) -> Self {
+        Self::seed_from_u64(self.rng.borrow_mut().generate())
+    }
+}
+
+impl Seeded for ThompsonSampling {
+    fn seed_from_u64(seed: u64) -> Self {
+        Self {
+            rng: RefCell::new(WyRand::new_seed(seed)),
+        }
+    }
+}
+
+impl<G: GameState> TreePolicy<G> for ThompsonSampling {
+    fn score(&self, parent: &MonteCarloNode<G>, child: &MonteCarloNode<G>) -> f64 {
+        let visits = child.visits() as f64;
+        let wins = child
+            .reward()
+            .get(&parent.state().current_player())
+            .map(|r| (*r).into())
+            .unwrap_or_default()
+            .clamp(0.0, visits);
+
+        let mut rng = self.rng.borrow_mut();
+        sample_beta(&mut rng, 1.0 + wins, 1.0 + visits - wins)
+    }
+}
+
+fn sample_beta(rng: &mut WyRand, alpha: f64, beta: f64) -> f64 {
+    let x = sample_gamma(rng, alpha);
+    let y = sample_gamma(rng, beta);
+    x / (x + y)
+}
+
+/// Marsaglia and Tsang's method, valid for `shape >= 1`.
+fn sample_gamma(rng: &mut WyRand, shape: f64) -> f64 {
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        let x = sample_normal(rng);
+        let v = (1.0 + c * x).powi(3);
+        if v <= 0.0 {
+            continue;
+        }
+        let u = 1.0 - rng.generate::<f64>();
+        if u.ln() < 0.5 * x * x + d * (1.0 - v + v.ln()) {
+            return d * v;
+        }
+    }
+}
+
+/// Box-Muller transform.
+fn sample_normal(rng: &mut WyRand) -> f64 {
+    let u1 = 1.0 - rng.generate::<f64>();
+    let u2 = rng.generate::<f64>();
+    (-2.0 * u1.max(f64::MIN_POSITIVE).ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beta_samples_are_bounded() {
+        let mut rng = WyRand::new_seed(42);
+        for (alpha, beta) in [(1.0, 1.0), (1.0, 50.0), (50.0, 1.0), (3.5, 7.25)] {
+            for _ in 0..1000 {
+                let x = sample_beta(&mut rng, alpha, beta);
+                assert!((0.0..=1.0).contains(&x));
+            }
+        }
+    }
+
+    #[test]
+    fn beta_sample_mean() {
+        let mut rng = WyRand::new_seed(7);
+        let n = 20_000;
+        let mean = (0..n).map(|_| sample_beta(&mut rng, 2.0, 6.0)).sum::<f64>() / n as f64;
+        assert!((mean - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn thompson_clones_draw_differently() {
+        let draw = |policy: &ThompsonSampling| {
+            let mut rng = policy.rng.borrow_mut();
+            (0..4).map(|_| rng.generate::<u64>()).collect::<Vec<_>>()
+        };
+        let policy = ThompsonSampling::seed_from_u64(0);
+        let (first, second) = (policy.clone(), policy.clone());
+        assert_ne!(draw(&first), draw(&second));
+        assert_ne!(draw(&first), draw(&policy));
+
+        // but the same seed still gives the same clones
+        let again = ThompsonSampling::seed_from_u64(0);
+        assert_eq!(
+            draw(&ThompsonSampling::seed_from_u64(0).clone()),
+            draw(&again.clone())
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use crate::collections::{GameState, MctsNode, MonteCarloNode, MonteCarloTree};
+use petgraph::{graph::NodeIndex, visit::Bfs, Graph};
+use std::collections::HashMap;
+
+impl<G: GameState> MonteCarloTree<G> {
+    /// Moves the root along the edge for `action`, keeping the statistics gathered below it.
+    /// If that child was never expanded, the tree restarts from the resulting state.
+    pub fn advance(&mut self, action: &G::Action) -> Result<(), G::Error> {
+        if let Some(edge) = self.find_edge(self.root, action) {
+            let (_, child) = self
+                .graph
+                .edge_endpoints(edge)
+                .expect("edge to be in the tree");
+            self.promote(child);
+            return Ok(());
+        }
+
+        let state = self.root_state().clone();
+        let player = state.current_player();
+        let state = state.step(&player, action)?;
+        self.reset(state);
+        Ok(())
+    }
+
+    /// Moves the root to the node holding `state`, which may be any number of moves below the
+    /// current root. If the search never reached `state`, the tree restarts from it.
+    pub fn reroot(&mut self, state: G) {
+        let mut bfs = Bfs::new(&self.graph, self.root);
+        while let Some(ix) = bfs.next(&self.graph) {
+            if self.graph.node_weight(ix).map(MonteCarloNode::state) == Some(&state) {
+                self.promote(ix);
+                return;
+            }
+        }
+        self.reset(state);
+    }
+
+    pub(crate) fn root_state(&self) -> &G {
+        self.graph
+            .node_weight(self.root)
+            .expect("root to be in the tree")
+            .state()
+    }
+
+    /// starts over from `state`, with the same configuration, so a solver still proves the new
+    /// root if the game is already over.
+    fn reset(&mut self, state: G) {
+        let seed = self.next_seed();
+        *self = self.fresh(seed, state);
+    }
+
+    /// Makes `node` the root, dropping everything that is no longer reachable from it and
+    /// renumbering the remaining nodes so the new root is index 0.
+    fn promote(&mut self, node: NodeIndex) {
+        let mut old = std::mem::take(&mut self.graph);
+        let mut graph = Graph::with_capacity(old.node_count(), old.edge_count());
+        let mut indices = HashMap::new();
+
+        let mut bfs = Bfs::new(&old, node);
+        while let Some(ix) = bfs.next(&old) {
+            let weight = std::mem::take(&mut old[ix]);
+            indices.insert(ix, graph.add_node(weight));
+        }
+
+        for edge in old.raw_edges() {
+            if let (Some(&a), Some(&b)) = (indices.get(&edge.source()), indices.get(&edge.target()))
+            {
+                graph.add_edge(a, b, edge.weight.clone());
+            }
+        }
+
+        self.root = indices[&node];
+        self.graph = graph;
+        self.rebuild_transpositions();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::testing::Race;
+    use petgraph::{visit::EdgeRef, Direction::Outgoing};
+
+    fn searched_tree() -> MonteCarloTree<Race> {
+        let mut tree = MonteCarloTree::<Race>::seed_from_u64(0);
+        for _ in 0..500 {
+            tree.iterate(1.0).unwrap();
+        }
+        tree
+    }
+
+    #[test]
+    fn advance_keeps_subtree() {
+        let mut tree = searched_tree();
+        let edge = tree.find_edge(tree.root, &2).unwrap();
+        let child = tree.graph.edge_endpoints(edge).unwrap().1;
+        let visits = tree.graph[child].visits();
+        let descendants = {
+            let mut bfs = Bfs::new(&tree.graph, child);
+            std::iter::from_fn(|| bfs.next(&tree.graph)).count()
+        };
+
+        tree.advance(&2).unwrap();
+
+        assert_eq!(tree.root, NodeIndex::new(0));
+        assert_eq!(tree.graph.node_count(), descendants);
+        assert_eq!(tree.graph[tree.root].visits(), visits);
+        assert_eq!(tree.graph[tree.root].state().total, 2);
+        for edge in tree.graph.edge_references() {
+            assert_eq!(edge.weight().visits, tree.graph[edge.target()].visits());
+        }
+    }
+
+    #[test]
+    fn reroot_skips_several_moves() {
+        let mut tree = searched_tree();
+        let state = Race::default().step(&0, &1).unwrap().step(&1, &2).unwrap();
+
+        tree.reroot(state.clone());
+
+        assert_eq!(tree.graph[tree.root].state(), &state);
+        assert!(tree.graph[tree.root].visits() > 0);
+        assert!(tree.graph.neighbors_directed(tree.root, Outgoing).count() > 0);
+    }
+
+    #[test]
+    fn unexpanded_moves_start_fresh() {
+        let mut tree = MonteCarloTree::<Race>::seed_from_u64(0);
+        tree.advance(&1).unwrap();
+        assert_eq!(tree.graph.node_count(), 1);
+        assert_eq!(tree.graph[tree.root].state().total, 1);
+        assert_eq!(tree.graph[tree.root].visits(), 0);
+
+        tree.reroot(Race::new(4));
+        assert_eq!(tree.graph.node_count(), 1);
+        assert_eq!(tree.graph[tree.root].state(), &Race::new(4));
+
+        assert!(tree.advance(&3).is_err());
+    }
+
+    #[test]
+    fn finished_games_are_proven_after_a_miss() {
+        let mut tree = MonteCarloTree::<Race>::seed_from_state(0, Race::new(4))
+            .with_solver()
+            .with_transpositions();
+        tree.reroot(Race::new(4).step(&0, &2).unwrap().step(&1, &2).unwrap());
+        assert!(tree.has_solver() && tree.has_transpositions());
+        assert!(tree.root_proof().unwrap().is_win_for(&1));
+
+        let mut tree = MonteCarloTree::<Race>::seed_from_state(0, Race::new(1)).with_solver();
+        tree.advance(&1).unwrap();
+        assert!(tree.root_proof().unwrap().is_win_for(&0));
+    }
+}
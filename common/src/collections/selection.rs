@@ -0,0 +1,236 @@
+use crate::collections::{GameState, MctsNode, MonteCarloTree, Proof};
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction::Outgoing};
+use std::cmp::Ordering;
+
+/// How to turn the statistics at the root into a decision once the search is over.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum MoveSelection {
+    /// The child with the highest mean reward.
+    MaxChild,
+    /// The child with the most visits.
+    #[default]
+    RobustChild,
+    /// The child with both the most visits and the highest mean reward. When no child leads on
+    /// both there is no choice yet, and the search should go on until one does.
+    MaxRobustChild,
+    /// The child maximizing the lower confidence bound `mean - a / sqrt(visits)`.
+    SecureChild(f64),
+}
+
+/// Search statistics for one child of the root, from the point of view of the player to move at
+/// the root.
+#[derive(Debug, Clone)]
+pub struct ChildStats<G: GameState> {
+    pub node: NodeIndex,
+    pub player: G::Player,
+    pub action: G::Action,
+    pub visits: u32,
+    pub mean_reward: f64,
+    /// The child's exact outcome, when the solver has proven it.
+    pub proof: Option<Proof<G>>,
+}
+
+impl<G: GameState> ChildStats<G> {
+    pub fn lower_bound(&self, a: f64) -> f64 {
+        if self.visits == 0 {
+            f64::NEG_INFINITY
+        } else {
+            self.mean_reward - a / (self.visits as f64).sqrt()
+        }
+    }
+}
+
+/// The action chosen at the root, along with the statistics of every root child it was chosen
+/// from.
+#[derive(Debug, Clone)]
+pub struct BestAction<G: GameState> {
+    pub player: G::Player,
+    pub action: G::Action,
+    pub node: NodeIndex,
+    pub children: Vec<ChildStats<G>>,
+}
+
+impl<G: GameState> MonteCarloTree<G> {
+    /// Statistics for every expanded child of the root.
+    pub fn root_stats(&self) -> Vec<ChildStats<G>> {
+        let Some(root) = self.graph.node_weight(self.root) else {
+            return Vec::new();
+        };
+        let mover = root.state().current_player();
+
+        let mut children = self
+            .graph
+            .edges_directed(self.root, Outgoing)
+            .filter_map(|edge| {
+                let child = self.graph.node_weight(edge.target())?;
+                Some(ChildStats {
+                    node: edge.target(),
+                    player: edge.weight().player.clone(),
+                    action: edge.weight().action.clone(),
+                    visits: child.visits(),
+                    mean_reward: child.mean_reward(&mover),
+                    proof: child.proof().cloned(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // petgraph walks edges newest first
+        children.reverse();
+        children
+    }
+
+    /// The action leading to the root child picked by `selection`, or `None` if the root has not
+    /// been expanded or `MaxRobustChild` finds no child leading on both counts. Children the
+    /// solver has proven override the statistics: a proven win is always taken, and proven losses
+    /// are only chosen when nothing else is left.
+    pub fn best_action(&self, selection: MoveSelection) -> Option<BestAction<G>> {
+        let all = self.root_stats();
+        let mover = self.graph.node_weight(self.root)?.state().current_player();
+        let keep = |check: fn(&Proof<G>, &G::Player) -> bool| {
+            all.iter()
+                .filter(|c| c.proof.as_ref().is_some_and(|proof| check(proof, &mover)))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        let wins = keep(Proof::is_win_for);
+        let unrefuted = keep(|proof, mover| !proof.is_loss_for(mover))
+            .into_iter()
+            .chain(all.iter().filter(|c| c.proof.is_none()).cloned())
+            .collect::<Vec<_>>();
+        let children = if !wins.is_empty() {
+            wins
+        } else if !unrefuted.is_empty() {
+            unrefuted
+        } else {
+            all.clone()
+        };
+
+        let most_visits = children.iter().map(|c| c.visits).max()?;
+        let best_mean = children
+            .iter()
+            .filter(|c| c.visits > 0)
+            .map(|c| c.mean_reward)
+            .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let robust = || children.iter().find(|c| c.visits == most_visits);
+        let chosen = match selection {
+            MoveSelection::MaxChild => children
+                .iter()
+                .filter(|c| c.visits > 0)
+                .find(|c| Some(c.mean_reward) == best_mean)
+                .or_else(robust),
+            MoveSelection::RobustChild => robust(),
+            MoveSelection::MaxRobustChild => children
+                .iter()
+                .find(|c| c.visits == most_visits && Some(c.mean_reward) == best_mean),
+            MoveSelection::SecureChild(a) => children.iter().reduce(|best, c| {
+                if c.lower_bound(a) > best.lower_bound(a) {
+                    c
+                } else {
+                    best
+                }
+            }),
+        }?;
+
+        Some(BestAction {
+            player: chosen.player.clone(),
+            action: chosen.action.clone(),
+            node: chosen.node,
+            children: all,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// A single decision between three actions.
+    #[derive(Debug, Default, Clone, PartialEq, Eq)]
+    struct OneShot {
+        choice: Option<u8>,
+    }
+
+    impl GameState for OneShot {
+        type Reward = f64;
+        type Action = u8;
+        type Player = u8;
+        type ActionIter = std::vec::IntoIter<(Self::Player, Self::Action)>;
+        type Error = ();
+
+        fn current_player(&self) -> Self::Player {
+            0
+        }
+
+        fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
+            HashMap::default()
+        }
+
+        fn is_complete(&self) -> bool {
+            self.choice.is_some()
+        }
+
+        fn action_iter(&self) -> Self::ActionIter {
+            vec![(0, 0), (0, 1), (0, 2)].into_iter()
+        }
+
+        fn step(self, _: &Self::Player, action: &Self::Action) -> Result<Self, Self::Error> {
+            Ok(Self {
+                choice: Some(*action),
+            })
+        }
+    }
+
+    /// Expands the root and sets `(visits, total reward)` for each child in action order.
+    fn tree_with_stats(stats: [(u32, f64); 3]) -> MonteCarloTree<OneShot> {
+        let mut tree = MonteCarloTree::<OneShot>::seed_from_u64(0);
+        tree.expand().unwrap();
+        for (child, (visits, reward)) in tree.root_stats().into_iter().zip(stats) {
+            let node = tree.graph.node_weight_mut(child.node).unwrap();
+            *node.visits_mut() = visits;
+            node.reward_mut().insert(0, reward);
+        }
+        *tree.graph.node_weight_mut(tree.root).unwrap().visits_mut() = 100;
+        tree
+    }
+
+    #[test]
+    fn root_stats_recover_actions() {
+        let tree = tree_with_stats([(10, 5.0), (20, 8.0), (70, 42.0)]);
+        let stats = tree.root_stats();
+        let actions = stats.iter().map(|c| c.action).collect::<Vec<_>>();
+        assert_eq!(actions, vec![0, 1, 2]);
+        assert_eq!(stats[2].visits, 70);
+        assert_eq!(stats[2].mean_reward, 0.6);
+    }
+
+    #[test]
+    fn selection_strategies() {
+        // action 0 has the best mean from few visits, action 2 is the most visited
+        let tree = tree_with_stats([(2, 2.0), (30, 15.0), (68, 34.0)]);
+        let pick = |selection| tree.best_action(selection).unwrap().action;
+        assert_eq!(pick(MoveSelection::MaxChild), 0);
+        assert_eq!(pick(MoveSelection::RobustChild), 2);
+        assert_eq!(pick(MoveSelection::SecureChild(1.0)), 2);
+        // so neither leads on both
+        assert!(tree.best_action(MoveSelection::MaxRobustChild).is_none());
+
+        let tree = tree_with_stats([(10, 1.0), (20, 16.0), (70, 42.0)]);
+        let pick = |selection| tree.best_action(selection).unwrap().action;
+        assert_eq!(pick(MoveSelection::MaxChild), 1);
+        assert_eq!(pick(MoveSelection::SecureChild(0.1)), 1);
+        assert_eq!(pick(MoveSelection::SecureChild(4.0)), 2);
+
+        let tree = tree_with_stats([(10, 1.0), (20, 8.0), (70, 49.0)]);
+        let pick = |selection| tree.best_action(selection).unwrap().action;
+        assert_eq!(pick(MoveSelection::MaxRobustChild), 2);
+    }
+
+    #[test]
+    fn unexpanded_root_has_no_action() {
+        let tree = MonteCarloTree::<OneShot>::seed_from_u64(0);
+        assert!(tree.best_action(MoveSelection::default()).is_none());
+    }
+}
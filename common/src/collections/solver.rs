@@ -0,0 +1,226 @@
+use crate::collections::{GameState, MctsNode, MonteCarloTree};
+use petgraph::{graph::NodeIndex, Direction::Outgoing};
+use std::collections::HashMap;
+
+/// The exact outcome of a node under perfect play, as established by the MCTS-Solver.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proof<G: GameState> {
+    /// The player who wins with perfect play, or `None` for a draw.
+    pub winner: Option<G::Player>,
+    /// The reward of the terminal state that perfect play leads to.
+    pub reward: HashMap<G::Player, G::Reward>,
+}
+
+/// How a finished game ended, as reported by `GameState::result`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameResult<P> {
+    Win(P),
+    Draw,
+}
+
+impl<G: GameState> Proof<G> {
+    /// The proof for a finished game, read off the result it reports along with its rewards.
+    /// `None` for games that don't report a result.
+    pub fn from_state(state: &G) -> Option<Self> {
+        let winner = match state.result()? {
+            GameResult::Win(player) => Some(player),
+            GameResult::Draw => None,
+        };
+        Some(Self {
+            winner,
+            reward: state.reward(),
+        })
+    }
+
+    pub fn is_win_for(&self, player: &G::Player) -> bool {
+        self.winner.as_ref() == Some(player)
+    }
+
+    pub fn is_loss_for(&self, player: &G::Player) -> bool {
+        self.winner.as_ref().is_some_and(|winner| winner != player)
+    }
+
+    pub fn is_draw(&self) -> bool {
+        self.winner.is_none()
+    }
+}
+
+impl<G: GameState> MonteCarloTree<G> {
+    /// The exact outcome at the root, once the solver has proven it.
+    pub fn root_proof(&self) -> Option<&Proof<G>> {
+        self.graph.node_weight(self.root)?.proof()
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.root_proof().is_some()
+    }
+
+    /// The proof for a node holding `state`, if `state` ends the game.
+    pub(crate) fn terminal_proof(state: &G) -> Option<Proof<G>> {
+        if state.is_complete() || (!state.is_chance() && state.action_iter().next().is_none()) {
+            Proof::from_state(state)
+        } else {
+            None
+        }
+    }
+
+    /// Children of `node` worth descending into: proven losses for the player to move are
+    /// dropped, unless nothing else is left.
+    pub(crate) fn unrefuted_children(
+        &self,
+        node: NodeIndex,
+        children: Vec<NodeIndex>,
+    ) -> Vec<NodeIndex> {
+        let mover = self.graph[node].state().current_player();
+        let open = children
+            .iter()
+            .copied()
+            .filter(|&ix| {
+                !self.graph[ix]
+                    .proof()
+                    .is_some_and(|proof| proof.is_loss_for(&mover))
+            })
+            .collect::<Vec<_>>();
+
+        if open.is_empty() {
+            children
+        } else {
+            open
+        }
+    }
+
+    /// Works back up `path` applying the minimax rules: a node is won if any child is a win for
+    /// the player to move, and otherwise proven once every child is, preferring a draw to a
+    /// loss. The rules assume two players; with more, an all-losing node credits the first
+    /// child's winner.
+    pub(crate) fn update_proofs(&mut self, path: &[NodeIndex]) {
+        for &ix in path.iter().rev() {
+            if self.graph[ix].proof().is_some() {
+                continue;
+            }
+            match self.prove(ix) {
+                Some(proof) => *self.graph[ix].proof_mut() = Some(proof),
+                None => break,
+            }
+        }
+    }
+
+    fn prove(&self, node: NodeIndex) -> Option<Proof<G>> {
+        let mover = self.graph[node].state().current_player();
+        let proofs = self
+            .graph
+            .neighbors_directed(node, Outgoing)
+            .map(|ix| self.graph[ix].proof())
+            .collect::<Vec<_>>();
+
+        // nobody chooses at a chance node, so it is only proven if every outcome agrees
+        if self.graph[node].state().is_chance() {
+            let proofs = proofs.into_iter().collect::<Option<Vec<_>>>()?;
+            let first = proofs.first()?;
+            return proofs
+                .iter()
+                .all(|proof| proof.winner == first.winner)
+                .then(|| (*first).clone());
+        }
+
+        if let Some(win) = proofs
+            .iter()
+            .flatten()
+            .find(|proof| proof.is_win_for(&mover))
+        {
+            return Some((*win).clone());
+        }
+
+        let proofs = proofs.into_iter().collect::<Option<Vec<_>>>()?;
+        proofs
+            .iter()
+            .find(|proof| proof.is_draw())
+            .or_else(|| proofs.first())
+            .map(|proof| (*proof).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::{
+        testing::{Gamble, Race},
+        SearchBudget, StopReason, Ucb1,
+    };
+
+    fn solve(state: Race) -> MonteCarloTree<Race> {
+        let mut tree = MonteCarloTree::seed_from_state(0, state).with_solver();
+        let summary = tree.search(&Ucb1::default(), &SearchBudget::new().iterations(100_000));
+        assert_eq!(summary.stop, StopReason::Solved);
+        tree
+    }
+
+    #[test]
+    fn proves_reported_results() {
+        let win = Proof::from_state(&Race::new(1).step(&0, &1).unwrap()).unwrap();
+        assert!(win.is_win_for(&0) && win.is_loss_for(&1));
+        assert_eq!(win.reward, HashMap::from([(0, 1.0)]));
+        assert_eq!(Proof::from_state(&Race::new(2)), None);
+
+        // a single player's score is no result, even when it is exactly one point
+        let paid = Gamble::new(100)
+            .step(&0, &Gamble::RISKY)
+            .and_then(|game| game.step(&0, &1))
+            .unwrap();
+        assert_eq!(paid.reward(), HashMap::from([(0, 1.0)]));
+        assert_eq!(Proof::from_state(&paid), None);
+    }
+
+    #[test]
+    fn solves_winning_and_losing_positions() {
+        // the player to move wins unless the distance to the target is a multiple of three
+        for target in 4..=13 {
+            let tree = solve(Race::new(target));
+            let proof = tree.root_proof().unwrap();
+            if target % 3 == 0 {
+                assert!(proof.is_win_for(&1), "target {}", target);
+            } else {
+                assert!(proof.is_win_for(&0), "target {}", target);
+                let best = tree.best_action(Default::default()).unwrap();
+                assert_eq!(best.action, target % 3, "target {}", target);
+            }
+        }
+    }
+
+    #[test]
+    fn solves_with_transpositions() {
+        let mut tree = MonteCarloTree::seed_from_state(0, Race::new(20))
+            .with_solver()
+            .with_transpositions();
+        let summary = tree.search(&Ucb1::default(), &SearchBudget::new().iterations(100_000));
+        assert_eq!(summary.stop, StopReason::Solved);
+        assert!(tree.root_proof().unwrap().is_win_for(&0));
+        assert_eq!(tree.best_action(Default::default()).unwrap().action, 2);
+    }
+
+    #[test]
+    fn selection_avoids_proven_losses() {
+        // both moves lose, so the root stays open until each of them is proven
+        let mut tree = MonteCarloTree::seed_from_state(0, Race::new(9)).with_solver();
+        let mut frozen = None;
+        while !tree.is_solved() {
+            tree.iterate(1.0).unwrap();
+
+            let children = tree.root_stats();
+            match frozen {
+                Some((action, visits)) => {
+                    let child = children.iter().find(|c| c.action == action).unwrap();
+                    assert_eq!(child.visits, visits);
+                }
+                None => {
+                    frozen = children
+                        .iter()
+                        .find(|c| c.proof.is_some())
+                        .map(|c| (c.action, c.visits));
+                }
+            }
+        }
+        assert!(frozen.is_some());
+        assert!(tree.root_proof().unwrap().is_win_for(&1));
+    }
+}
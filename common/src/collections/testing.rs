@@ -0,0 +1,163 @@
+use crate::collections::{GameResult, GameState};
+use std::collections::HashMap;
+
+/// Two players take turns adding one or two to a running total, and whoever reaches `target`
+/// wins. The player to move loses with perfect play whenever the distance left to the target is
+/// a multiple of three.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Race {
+    pub total: u8,
+    pub player: u8,
+    pub target: u8,
+}
+
+impl Race {
+    pub fn new(target: u8) -> Self {
+        Self {
+            total: 0,
+            player: 0,
+            target,
+        }
+    }
+}
+
+impl Default for Race {
+    fn default() -> Self {
+        Self::new(10)
+    }
+}
+
+impl GameState for Race {
+    type Reward = f64;
+    type Action = u8;
+    type Player = u8;
+    type ActionIter = std::vec::IntoIter<(Self::Player, Self::Action)>;
+    type Error = ();
+
+    fn current_player(&self) -> Self::Player {
+        self.player
+    }
+
+    fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
+        if self.is_complete() {
+            HashMap::from([(1 - self.player, 1.0)])
+        } else {
+            HashMap::default()
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.total >= self.target
+    }
+
+    fn action_iter(&self) -> Self::ActionIter {
+        let actions = if self.is_complete() {
+            vec![]
+        } else if self.total + 1 == self.target {
+            vec![(self.player, 1)]
+        } else {
+            vec![(self.player, 1), (self.player, 2)]
+        };
+        actions.into_iter()
+    }
+
+    fn step(self, player: &Self::Player, action: &Self::Action) -> Result<Self, Self::Error> {
+        if *player != self.player || !(1..=2).contains(action) || self.is_complete() {
+            return Err(());
+        }
+        Ok(Self {
+            total: (self.total + action).min(self.target),
+            player: 1 - self.player,
+            target: self.target,
+        })
+    }
+
+    fn result(&self) -> Option<GameResult<Self::Player>> {
+        self.is_complete().then(|| GameResult::Win(1 - self.player))
+    }
+}
+
+/// A single player picks between a sure half point and a gamble that pays one point with the
+/// given chance, in percent.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Gamble {
+    pub percent: u8,
+    pub choice: Option<u8>,
+    pub payout: Option<u8>,
+}
+
+impl Gamble {
+    pub const SAFE: u8 = 0;
+    pub const RISKY: u8 = 1;
+
+    pub fn new(percent: u8) -> Self {
+        Self {
+            percent,
+            choice: None,
+            payout: None,
+        }
+    }
+}
+
+impl Default for Gamble {
+    fn default() -> Self {
+        Self::new(50)
+    }
+}
+
+impl GameState for Gamble {
+    type Reward = f64;
+    type Action = u8;
+    type Player = u8;
+    type ActionIter = std::vec::IntoIter<(Self::Player, Self::Action)>;
+    type Error = ();
+
+    fn current_player(&self) -> Self::Player {
+        0
+    }
+
+    fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
+        match (self.choice, self.payout) {
+            (Some(Self::SAFE), _) => HashMap::from([(0, 0.5)]),
+            (_, Some(payout)) => HashMap::from([(0, payout as f64)]),
+            _ => HashMap::default(),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.choice == Some(Self::SAFE) || self.payout.is_some()
+    }
+
+    fn action_iter(&self) -> Self::ActionIter {
+        if self.choice.is_none() {
+            vec![(0, Self::SAFE), (0, Self::RISKY)].into_iter()
+        } else {
+            vec![].into_iter()
+        }
+    }
+
+    fn is_chance(&self) -> bool {
+        self.choice == Some(Self::RISKY) && self.payout.is_none()
+    }
+
+    fn chance_outcomes(&self) -> Vec<(Self::Player, Self::Action, f64)> {
+        let p = self.percent as f64 / 100.0;
+        vec![(0, 1, p), (0, 0, 1.0 - p)]
+    }
+
+    fn step(self, _: &Self::Player, action: &Self::Action) -> Result<Self, Self::Error> {
+        if self.is_chance() {
+            Ok(Self {
+                payout: Some(*action),
+                ..self
+            })
+        } else if self.choice.is_none() && *action <= Self::RISKY {
+            Ok(Self {
+                choice: Some(*action),
+                ..self
+            })
+        } else {
+            Err(())
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::collections::{GameState, MctsNode, MonteCarloEdge, MonteCarloNode};
+use petgraph::{graph::NodeIndex, Graph};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+/// Index from state hashes to the node already holding that state, which lets the tree reuse a
+/// node when a position is reached through a different move order.
+#[derive(Debug)]
+pub(crate) struct TranspositionTable<G> {
+    hash: fn(&G) -> u64,
+    table: HashMap<u64, NodeIndex>,
+}
+
+impl<G: GameState> TranspositionTable<G> {
+    pub(crate) fn new() -> Self
+    where
+        G: Hash,
+    {
+        Self {
+            hash: hash_state::<G>,
+            table: HashMap::new(),
+        }
+    }
+
+    /// A table using the same hash function, with no entries.
+    pub(crate) fn emptied(&self) -> Self {
+        Self {
+            hash: self.hash,
+            table: HashMap::new(),
+        }
+    }
+
+    /// The node holding `state`, if there is one. Hash collisions between different states are
+    /// treated as misses.
+    pub(crate) fn get(
+        &self,
+        graph: &Graph<MonteCarloNode<G>, MonteCarloEdge<G>>,
+        state: &G,
+    ) -> Option<NodeIndex> {
+        let ix = *self.table.get(&(self.hash)(state))?;
+        (graph.node_weight(ix)?.state() == state).then_some(ix)
+    }
+
+    /// Records that `ix` holds `state`, unless another state with the same hash got there first.
+    pub(crate) fn insert(&mut self, state: &G, ix: NodeIndex) {
+        self.table.entry((self.hash)(state)).or_insert(ix);
+    }
+
+    pub(crate) fn rebuild(&mut self, graph: &Graph<MonteCarloNode<G>, MonteCarloEdge<G>>) {
+        self.table.clear();
+        for ix in graph.node_indices() {
+            self.insert(graph[ix].state(), ix);
+        }
+    }
+}
+
+fn hash_state<G: Hash>(state: &G) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collections::{testing::Race, GameState, MctsNode, MonteCarloTree};
+    use petgraph::Direction::Incoming;
+
+    fn searched(transpositions: bool) -> MonteCarloTree<Race> {
+        let mut tree = MonteCarloTree::<Race>::seed_from_u64(0);
+        if transpositions {
+            tree = tree.with_transpositions();
+        }
+        for _ in 0..2000 {
+            tree.iterate(1.0).unwrap();
+        }
+        tree
+    }
+
+    #[test]
+    fn repeated_states_share_a_node() {
+        let tree = searched(true);
+        // one node per (total, player to move), at most
+        assert!(tree.graph.node_count() <= 22);
+        assert!(searched(false).graph.node_count() > 50);
+
+        let mut states = tree
+            .graph
+            .node_weights()
+            .map(|node| node.state().clone())
+            .collect::<Vec<_>>();
+        let count = states.len();
+        states.sort_by_key(|state| (state.total, state.player));
+        states.dedup();
+        assert_eq!(states.len(), count);
+    }
+
+    #[test]
+    fn visits_arrive_through_one_parent_at_a_time() {
+        let tree = searched(true);
+        for ix in tree.graph.node_indices().filter(|&ix| ix != tree.root) {
+            let incoming = tree
+                .graph
+                .edges_directed(ix, Incoming)
+                .map(|edge| edge.weight().visits)
+                .sum::<u32>();
+            assert_eq!(incoming, tree.graph[ix].visits());
+        }
+        assert!(tree.graph.node_indices().any(|ix| tree
+            .graph
+            .edges_directed(ix, Incoming)
+            .count()
+            > 1));
+    }
+
+    #[test]
+    fn lines_and_decisions_work_through_shared_nodes() {
+        let mut tree =
+            MonteCarloTree::<Race>::seed_from_state(0, Race::new(14)).with_transpositions();
+        for _ in 0..3000 {
+            tree.iterate(1.0).unwrap();
+        }
+        assert_eq!(tree.best_action(Default::default()).unwrap().action, 2);
+
+        for ix in tree.graph.node_indices() {
+            let state = tree
+                .line_to(ix)
+                .unwrap()
+                .iter()
+                .fold(Race::new(14), |state, (player, action)| {
+                    state.step(player, action).unwrap()
+                });
+            assert_eq!(&state, tree.graph[ix].state());
+        }
+
+        tree.advance(&2).unwrap();
+        assert!(tree.has_transpositions());
+        for _ in 0..100 {
+            tree.iterate(1.0).unwrap();
+        }
+        assert!(tree.graph.node_count() <= 26);
+    }
+}
@@ -1,19 +1,36 @@
+use crate::collections::{
+    chance::sample_outcome, transposition::TranspositionTable, GameResult, Proof, TreePolicy, Ucb1,
+};
 use nanorand::{tls_rng, Rng, WyRand};
 use petgraph::{
-    graph::NodeIndex,
+    graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
     Direction::{Incoming, Outgoing},
     Graph,
 };
-use std::{collections::HashMap, hash::Hash, marker::PhantomData, ops::Add};
+use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    hash::Hash,
+    marker::PhantomData,
+    ops::Add,
+};
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct MonteCarloTree<G: GameState> {
     rng: WyRand,
     pub root: NodeIndex,
-    pub graph: Graph<MonteCarloNode<G>, ()>,
+    pub graph: Graph<MonteCarloNode<G>, MonteCarloEdge<G>>,
+    transpositions: Option<TranspositionTable<G>>,
+    solver: bool,
+    reward_map: Option<RewardMap<G>>,
     _state: PhantomData<G>,
 }
 
+/// Turns the rewards a game reports into the ones the search backs up.
+pub type RewardMap<G> = fn(
+    HashMap<<G as GameState>::Player, <G as GameState>::Reward>,
+) -> HashMap<<G as GameState>::Player, <G as GameState>::Reward>;
+
 impl<G: GameState> MonteCarloTree<G> {
     pub fn new() -> Self {
         let seed = tls_rng().generate();
@@ -21,39 +38,155 @@ impl<G: GameState> MonteCarloTree<G> {
     }
 
     pub fn seed_from_u64(seed: u64) -> Self {
+        Self::seed_from_state(seed, G::default())
+    }
+
+    pub fn from_state(state: G) -> Self {
+        let seed = tls_rng().generate();
+        Self::seed_from_state(seed, state)
+    }
+
+    pub fn seed_from_state(seed: u64, state: G) -> Self {
         let rng = WyRand::new_seed(seed);
         let mut graph = Graph::new();
-        let root = graph.add_node(MonteCarloNode::default());
+        let root = graph.add_node(MonteCarloNode::from_state(state));
         Self {
             rng,
             root,
             graph,
+            transpositions: None,
+            solver: false,
+            reward_map: None,
             _state: PhantomData,
         }
     }
 
+    /// Share nodes between move orders that reach the same state, turning the tree into a DAG.
+    /// Statistics of a shared node pool every line that passes through it.
+    pub fn with_transpositions(mut self) -> Self
+    where
+        G: Hash,
+    {
+        let mut table = TranspositionTable::new();
+        table.rebuild(&self.graph);
+        self.transpositions = Some(table);
+        self
+    }
+
+    pub fn has_transpositions(&self) -> bool {
+        self.transpositions.is_some()
+    }
+
+    /// Prove terminal positions and propagate their exact outcomes up the tree (MCTS-Solver).
+    /// Selection then steers clear of proven losses, and `search` stops once the root is proven.
+    /// Only games that report their results through `GameState::result` get proven.
+    pub fn with_solver(mut self) -> Self {
+        self.solver = true;
+        for ix in self.graph.node_indices() {
+            if let Some(proof) = Self::terminal_proof(self.graph[ix].state()) {
+                *self.graph[ix].proof_mut() = Some(proof);
+            }
+        }
+        let root = self.root;
+        for ix in self.graph.node_indices() {
+            if let Some(path) = self.path_between(root, ix) {
+                self.update_proofs(&path);
+            }
+        }
+        self
+    }
+
+    pub fn has_solver(&self) -> bool {
+        self.solver
+    }
+
+    /// Passes every result through `map` before it is backed up, for example to change what a
+    /// draw is worth without changing the game. Proofs still come from `GameState::result`, so
+    /// they don't depend on the map.
+    pub fn with_reward_map(mut self, map: RewardMap<G>) -> Self {
+        self.reward_map = Some(map);
+        self
+    }
+
+    /// A new, empty tree from `state` with the same configuration as this one.
+    pub(crate) fn fresh(&self, seed: u64, state: G) -> Self {
+        let mut tree = Self::seed_from_state(seed, state);
+        tree.reward_map = self.reward_map;
+        if self.solver {
+            tree = tree.with_solver();
+        }
+        if let Some(table) = &self.transpositions {
+            let mut table = table.emptied();
+            table.rebuild(&tree.graph);
+            tree.transpositions = Some(table);
+        }
+        tree
+    }
+
+    /// Re-index the transposition table after nodes have been renumbered.
+    pub(crate) fn rebuild_transpositions(&mut self) {
+        if let Some(table) = self.transpositions.as_mut() {
+            table.rebuild(&self.graph);
+        }
+    }
+
+    /// Draws a seed for a new generator from the tree's own, so that helpers spawned by a seeded
+    /// tree are reproducible.
+    pub(crate) fn next_seed(&mut self) -> u64 {
+        self.rng.generate()
+    }
+
+    /// The UCB1 score of `node` from the point of view of the player choosing it at its parent.
+    /// Returns `None` for the root or for an index that is not in the tree.
     pub fn uct(&self, node: NodeIndex, c: f64) -> Option<f64> {
-        todo!()
+        let parent = self.graph.neighbors_directed(node, Incoming).next()?;
+        let parent = self.graph.node_weight(parent)?;
+        let child = self.graph.node_weight(node)?;
+        Some(Ucb1::new(c).score(parent, child))
     }
 
     pub fn iterate(&mut self, c: f64) -> Option<()> {
-        self.iterate_from(self.root, c)
+        self.iterate_with(&Ucb1::new(c))
     }
 
-    pub fn iterate_from(&mut self, start: NodeIndex, c: f64) -> Option<()> {
-        let node = self.select_from(start, c)?;
+    pub fn iterate_with<P: TreePolicy<G>>(&mut self, policy: &P) -> Option<()> {
+        self.iterate_from(self.root, policy)
+    }
 
-        if self.graph.node_weight(node)?.visits() == 0 {
-            let reward = self.graph.node_weight(node)?.rollout(&mut self.rng);
-            let _ = self.backpropagate_from(&reward, node, start);
-        } else {
+    pub fn iterate_from<P: TreePolicy<G>>(&mut self, start: NodeIndex, policy: &P) -> Option<()> {
+        let path = self.select_path(start, policy)?;
+        let node = *path.last()?;
+
+        // proven nodes are never searched below, they just report their exact outcome
+        if let Some(proof) = self.graph.node_weight(node)?.proof() {
+            let reward = proof.reward.clone();
+            let _ = self.backpropagate_path(&reward, &path);
+            return Some(());
+        }
+
+        if self.graph.node_weight(node)?.visits() > 0 && self.is_leaf(node) {
             self.expand_from(node)?;
+            if !self.is_leaf(node) {
+                if self.solver {
+                    self.update_proofs(&path);
+                }
+                return Some(());
+            }
         }
+
+        // unvisited leaves are rolled out, and so are terminal nodes, which never grow children,
+        // and nodes whose every child would lead back into the current path
+        let reward = self.graph.node_weight(node)?.rollout(&mut self.rng);
+        let _ = self.backpropagate_path(&reward, &path);
         Some(())
     }
 
     pub fn select(&self, c: f64) -> Option<NodeIndex> {
-        self.select_from(self.root, c)
+        self.select_with(&Ucb1::new(c))
+    }
+
+    pub fn select_with<P: TreePolicy<G>>(&self, policy: &P) -> Option<NodeIndex> {
+        self.select_from(self.root, policy)
     }
 
     pub fn expand(&mut self) -> Option<()> {
@@ -68,47 +201,104 @@ impl<G: GameState> MonteCarloTree<G> {
         self.backpropagate_from(result, leaf, self.root)
     }
 
-    pub fn select_from(&self, start: NodeIndex, c: f64) -> Option<NodeIndex> {
-        let parent_visits = self.graph.node_weight(start).map(MonteCarloNode::visits)?;
+    pub fn select_from<P: TreePolicy<G>>(&self, start: NodeIndex, policy: &P) -> Option<NodeIndex> {
+        self.select_path(start, policy)?.last().copied()
+    }
 
-        let best_child = self
-            .graph
-            .neighbors_directed(start, Outgoing)
-            .into_iter()
-            .filter_map(|ix| self.graph.node_weight(ix).map(|node| (ix, node)))
-            .map(|(ix, node)| {
-                let uct = node.uct(c, parent_visits);
-                (ix, uct)
-            })
-            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
-            .map(|(ix, _)| ix);
+    /// Every node visited while descending from `start` to a leaf, in order.
+    pub(crate) fn select_path<P: TreePolicy<G>>(
+        &self,
+        start: NodeIndex,
+        policy: &P,
+    ) -> Option<Vec<NodeIndex>> {
+        let mut path = vec![start];
+        loop {
+            let current = *path.last()?;
+            let parent = self.graph.node_weight(current)?;
+            if parent.proof().is_some() {
+                return Some(path);
+            }
+
+            if parent.state().is_chance() {
+                match self.select_outcome(current, &path) {
+                    None => return Some(path),
+                    Some(ix) => {
+                        path.push(ix);
+                        continue;
+                    }
+                }
+            }
+
+            // with transpositions a child can also be an ancestor, so skip anything already on
+            // the path rather than loop forever
+            let mut children = self
+                .graph
+                .neighbors_directed(current, Outgoing)
+                .filter(|ix| !path.contains(ix))
+                .collect::<Vec<_>>();
+            if self.solver {
+                children = self.unrefuted_children(current, children);
+            }
+
+            let best_child = children
+                .into_iter()
+                .filter_map(|ix| self.graph.node_weight(ix).map(|node| (ix, node)))
+                .map(|(ix, node)| (ix, policy.score(parent, node)))
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(ix, _)| ix);
 
-        match best_child {
-            None => Some(start),
-            Some(ix) => self.select_from(ix, c),
+            match best_child {
+                None => return Some(path),
+                Some(ix) => path.push(ix),
+            }
         }
     }
 
     pub fn expand_from(&mut self, node: NodeIndex) -> Option<()> {
         if self.graph.neighbors_directed(node, Outgoing).count() > 0 {
-            return None;
+            None
         } else {
             // generate all possible states from the current node
             let state = self.graph.node_weight(node)?.state();
-            let nodes = state
-                .action_iter()
-                .filter_map(|(player, action)| {
-                    state
-                        .clone()
-                        .step(&player, &action)
-                        .map(MonteCarloNode::from_state)
-                        .ok()
-                })
-                .collect::<Vec<_>>();
+            let children = if state.is_chance() {
+                state
+                    .chance_outcomes()
+                    .into_iter()
+                    .filter_map(|(player, action, probability)| {
+                        let child = state.clone().step(&player, &action).ok()?;
+                        Some((MonteCarloEdge::chance(player, action, probability), child))
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                state
+                    .action_iter()
+                    .filter_map(|(player, action)| {
+                        let child = state.clone().step(&player, &action).ok()?;
+                        Some((MonteCarloEdge::new(player, action), child))
+                    })
+                    .collect::<Vec<_>>()
+            };
 
-            for n in nodes {
-                let ix = self.graph.add_node(n);
-                let _ = self.graph.add_edge(node, ix, ());
+            for (edge, child) in children {
+                let existing = self
+                    .transpositions
+                    .as_ref()
+                    .and_then(|table| table.get(&self.graph, &child));
+                let ix = match existing {
+                    Some(ix) => ix,
+                    None => {
+                        let mut weight = MonteCarloNode::from_state(child);
+                        if self.solver {
+                            *weight.proof_mut() = Self::terminal_proof(weight.state());
+                        }
+                        let ix = self.graph.add_node(weight);
+                        if let Some(table) = self.transpositions.as_mut() {
+                            table.insert(self.graph[ix].state(), ix);
+                        }
+                        ix
+                    }
+                };
+                let _ = self.graph.add_edge(node, ix, edge);
             }
 
             Some(())
@@ -122,24 +312,66 @@ impl<G: GameState> MonteCarloTree<G> {
         root: NodeIndex,
     ) -> Option<()> {
         // is there a path from the leaf to the root?
-        let mut path = Vec::new();
-        path.push(leaf);
-        while let Some(ix) = self
-            .graph
-            .neighbors_directed(*path.last().unwrap(), Incoming)
-            .next()
-        {
-            path.push(ix);
+        let path = self.path_between(root, leaf)?;
+        self.backpropagate_path(result, &path)
+    }
+
+    /// The shortest chain of nodes from `from` down to `to`, found by walking up from `to`.
+    /// Nodes reachable through several parents only need one of them to lead back to `from`.
+    pub(crate) fn path_between(&self, from: NodeIndex, to: NodeIndex) -> Option<Vec<NodeIndex>> {
+        let mut parents = HashMap::from([(to, to)]);
+        let mut queue = VecDeque::from([to]);
+
+        while let Some(current) = queue.pop_front() {
+            if current == from {
+                let mut path = vec![from];
+                let mut ix = from;
+                while ix != to {
+                    ix = parents[&ix];
+                    path.push(ix);
+                }
+                return Some(path);
+            }
+            for parent in self.graph.neighbors_directed(current, Incoming) {
+                if let Entry::Vacant(entry) = parents.entry(parent) {
+                    entry.insert(current);
+                    queue.push_back(parent);
+                }
+            }
         }
+        None
+    }
 
-        if let Some(ix) = path.last() {
-            if *ix != root {
-                return None;
+    fn is_leaf(&self, node: NodeIndex) -> bool {
+        self.graph
+            .neighbors_directed(node, Outgoing)
+            .next()
+            .is_none()
+    }
+
+    /// Adds `result` to every node along `path`, which runs from the top of the tree down, and
+    /// to the edges connecting them.
+    pub(crate) fn backpropagate_path(
+        &mut self,
+        result: &HashMap<G::Player, G::Reward>,
+        path: &[NodeIndex],
+    ) -> Option<()> {
+        let mapped;
+        let result = match self.reward_map {
+            Some(map) => {
+                mapped = map(result.clone());
+                &mapped
             }
+            None => result,
+        };
+
+        for pair in path.windows(2) {
+            let edge = self.graph.find_edge(pair[0], pair[1])?;
+            self.graph.edge_weight_mut(edge)?.visits += 1;
         }
 
         // update the reward hashmap at each node in the path
-        for ix in path.into_iter() {
+        for &ix in path {
             let node = self.graph.node_weight_mut(ix)?;
             *node.visits_mut() += 1;
 
@@ -150,18 +382,105 @@ impl<G: GameState> MonteCarloTree<G> {
                     .entry(player.clone())
                     .or_insert_with(Default::default);
                 *entry = *entry + *reward;
+
+                let reward: f64 = (*reward).into();
+                let entry = node
+                    .squared_reward_mut()
+                    .entry(player.clone())
+                    .or_insert_with(Default::default);
+                *entry += reward * reward;
             }
         }
 
+        if self.solver {
+            self.update_proofs(path);
+        }
         Some(())
     }
 }
 
+impl<G: GameState> MonteCarloTree<G> {
+    /// The moves leading from the root to `node`, or `None` if `node` is not in the tree.
+    pub fn line_to(&self, node: NodeIndex) -> Option<Vec<(G::Player, G::Action)>> {
+        self.path_between(self.root, node)?
+            .windows(2)
+            .map(|pair| {
+                let edge = self.graph.find_edge(pair[0], pair[1])?;
+                let edge = self.graph.edge_weight(edge)?;
+                Some((edge.player.clone(), edge.action.clone()))
+            })
+            .collect()
+    }
+
+    /// The line of play the search currently expects, found by following the most visited edge
+    /// out of each node starting at the root.
+    pub fn principal_variation(&self) -> Vec<(G::Player, G::Action)> {
+        let mut line = Vec::new();
+        let mut current = self.root;
+        while let Some(edge) = self
+            .graph
+            .edges_directed(current, Outgoing)
+            .max_by_key(|edge| edge.weight().visits)
+        {
+            if edge.weight().visits == 0 {
+                break;
+            }
+            line.push((edge.weight().player.clone(), edge.weight().action.clone()));
+            current = edge.target();
+        }
+        line
+    }
+
+    /// The edge leading from `parent` to the child reached by playing `action`.
+    pub fn find_edge(&self, parent: NodeIndex, action: &G::Action) -> Option<EdgeIndex> {
+        self.graph
+            .edges_directed(parent, Outgoing)
+            .find(|edge| &edge.weight().action == action)
+            .map(|edge| edge.id())
+    }
+}
+
+impl<G: GameState> Default for MonteCarloTree<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The move connecting a node to one of its children, and the number of times the search has
+/// passed through it. Edges out of chance nodes also carry the outcome's probability.
+#[derive(Debug, Clone)]
+pub struct MonteCarloEdge<G: GameState> {
+    pub player: G::Player,
+    pub action: G::Action,
+    pub visits: u32,
+    pub probability: Option<f64>,
+}
+
+impl<G: GameState> MonteCarloEdge<G> {
+    pub fn new(player: G::Player, action: G::Action) -> Self {
+        Self {
+            player,
+            action,
+            visits: 0,
+            probability: None,
+        }
+    }
+
+    pub fn chance(player: G::Player, action: G::Action, probability: f64) -> Self {
+        Self {
+            probability: Some(probability),
+            ..Self::new(player, action)
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct MonteCarloNode<G: GameState> {
     state: G,
     visits: u32,
     reward: HashMap<G::Player, G::Reward>,
+    squared_reward: HashMap<G::Player, f64>,
+    proof: Option<Proof<G>>,
 }
 
 impl<G: GameState> MctsNode<G> for MonteCarloNode<G> {
@@ -170,6 +489,8 @@ impl<G: GameState> MctsNode<G> for MonteCarloNode<G> {
             state,
             visits: 0,
             reward: HashMap::default(),
+            squared_reward: HashMap::default(),
+            proof: None,
         }
     }
 
@@ -192,6 +513,22 @@ impl<G: GameState> MctsNode<G> for MonteCarloNode<G> {
     fn reward_mut(&mut self) -> &mut HashMap<G::Player, G::Reward> {
         &mut self.reward
     }
+
+    fn squared_reward(&self) -> &HashMap<G::Player, f64> {
+        &self.squared_reward
+    }
+
+    fn squared_reward_mut(&mut self) -> &mut HashMap<G::Player, f64> {
+        &mut self.squared_reward
+    }
+
+    fn proof(&self) -> Option<&Proof<G>> {
+        self.proof.as_ref()
+    }
+
+    fn proof_mut(&mut self) -> &mut Option<Proof<G>> {
+        &mut self.proof
+    }
 }
 
 pub trait MctsNode<G: GameState>: Default {
@@ -201,20 +538,23 @@ pub trait MctsNode<G: GameState>: Default {
     fn visits_mut(&mut self) -> &mut u32;
     fn reward(&self) -> &HashMap<G::Player, G::Reward>;
     fn reward_mut(&mut self) -> &mut HashMap<G::Player, G::Reward>;
+    fn squared_reward(&self) -> &HashMap<G::Player, f64>;
+    fn squared_reward_mut(&mut self) -> &mut HashMap<G::Player, f64>;
+    fn proof(&self) -> Option<&Proof<G>>;
+    fn proof_mut(&mut self) -> &mut Option<Proof<G>>;
 
-    fn uct(&self, c: f64, parent_visits: u32) -> f64 {
-        let parent_visits = parent_visits as f64;
+    /// The average reward `player` has collected from the rollouts through this node.
+    fn mean_reward(&self, player: &G::Player) -> f64 {
         let visits = self.visits() as f64;
-        let reward = self
-            .reward()
-            .get(&self.state().current_player())
-            .map(|r| (*r).into())
-            .unwrap_or_default();
-
         if visits == 0.0 {
-            f64::INFINITY
+            0.0
         } else {
-            reward / visits + c * (parent_visits.ln() / visits).sqrt()
+            let reward: f64 = self
+                .reward()
+                .get(player)
+                .map(|r| (*r).into())
+                .unwrap_or_default();
+            reward / visits
         }
     }
 
@@ -226,7 +566,12 @@ pub trait MctsNode<G: GameState>: Default {
                 return state.reward();
             }
 
-            let (player, action) = {
+            let (player, action) = if state.is_chance() {
+                match sample_outcome::<G>(state.chance_outcomes(), rng) {
+                    Some(outcome) => outcome,
+                    None => return HashMap::new(),
+                }
+            } else {
                 let actions = state.action_iter().collect::<Vec<_>>();
                 if actions.is_empty() {
                     return HashMap::new();
@@ -245,7 +590,7 @@ pub trait MctsNode<G: GameState>: Default {
 
 pub trait GameState: std::fmt::Debug + Default + Clone + PartialEq + Eq + Sized {
     type Reward: Add<Output = Self::Reward> + Into<f64> + Copy + Default + std::fmt::Debug;
-    type Action: Clone;
+    type Action: Clone + PartialEq + std::fmt::Debug;
     type Player: Clone + Hash + Eq + Default + std::fmt::Debug;
     type ActionIter: Iterator<Item = (Self::Player, Self::Action)>;
     type Error;
@@ -255,11 +600,33 @@ pub trait GameState: std::fmt::Debug + Default + Clone + PartialEq + Eq + Sized
     fn is_complete(&self) -> bool;
     fn action_iter(&self) -> Self::ActionIter;
     fn step(self, player: &Self::Player, action: &Self::Action) -> Result<Self, Self::Error>;
+
+    /// Whether the next transition is a random event rather than a decision. Chance states list
+    /// their possible outcomes in `chance_outcomes`, and each outcome is applied with `step`.
+    fn is_chance(&self) -> bool {
+        false
+    }
+
+    /// Every outcome of a chance state with its probability. The probabilities should sum to one.
+    fn chance_outcomes(&self) -> Vec<(Self::Player, Self::Action, f64)> {
+        Vec::new()
+    }
+
+    /// Who won a finished game, or whether it was drawn, for the MCTS-Solver to prove. Games
+    /// that aren't won or lost, such as single-player scoring games, keep the default `None` and
+    /// are never proven.
+    fn result(&self) -> Option<GameResult<Self::Player>> {
+        None
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        collections::{Puct, ThompsonSampling, Ucb1Tuned},
+        traits::Seeded,
+    };
 
     #[derive(Debug, Clone, Eq, PartialEq)]
     struct MockGame {
@@ -336,4 +703,109 @@ mod tests {
         tree.expand_from(node).unwrap();
         assert_eq!(tree.graph.node_count(), 5);
     }
+
+    #[test]
+    fn uct_matches_ucb1() {
+        let mut tree = MonteCarloTree::<MockGame>::seed_from_u64(0);
+        for _ in 0..50 {
+            tree.iterate(2.0).unwrap();
+        }
+        assert_eq!(tree.uct(tree.root, 2.0), None);
+
+        let parent = tree.graph.node_weight(tree.root).unwrap();
+        for ix in tree.graph.neighbors_directed(tree.root, Outgoing) {
+            let child = tree.graph.node_weight(ix).unwrap();
+            let expected = child.mean_reward(&0)
+                + 2.0 * ((parent.visits() as f64).ln() / child.visits() as f64).sqrt();
+            assert_eq!(tree.uct(ix, 2.0), Some(expected));
+        }
+    }
+
+    #[test]
+    fn iterate_with_each_policy() {
+        fn run<P: TreePolicy<MockGame>>(policy: &P) {
+            let mut tree = MonteCarloTree::<MockGame>::seed_from_u64(0);
+            for _ in 0..100 {
+                tree.iterate_with(policy).unwrap();
+            }
+            let root = tree.graph.node_weight(tree.root).unwrap();
+            assert!(root.visits() > 50);
+            assert!(tree.graph.node_count() > 3);
+        }
+
+        run(&Ucb1::default());
+        run(&Ucb1Tuned::new());
+        run(&Puct::uniform(1.5));
+        run(&ThompsonSampling::seed_from_u64(0));
+    }
+
+    #[test]
+    fn edges_carry_actions_and_visits() {
+        let mut tree = MonteCarloTree::<MockGame>::seed_from_u64(0);
+        for _ in 0..100 {
+            tree.iterate(2.0).unwrap();
+        }
+
+        let mut actions = tree
+            .graph
+            .edges_directed(tree.root, Outgoing)
+            .map(|edge| edge.weight().action)
+            .collect::<Vec<_>>();
+        actions.sort();
+        assert_eq!(actions, vec![-1, 1]);
+
+        for edge in tree.graph.edge_references() {
+            let child = tree.graph.node_weight(edge.target()).unwrap();
+            assert_eq!(edge.weight().visits, child.visits());
+        }
+    }
+
+    #[test]
+    fn replay_lines_from_root() {
+        let mut tree = MonteCarloTree::<MockGame>::seed_from_u64(0);
+        for _ in 0..200 {
+            tree.iterate(2.0).unwrap();
+        }
+
+        let pv = tree.principal_variation();
+        assert!(!pv.is_empty());
+
+        for node in tree.graph.node_indices() {
+            let line = tree.line_to(node).unwrap();
+            let state = line
+                .iter()
+                .fold(MockGame::default(), |state, (player, action)| {
+                    state.step(player, action).unwrap()
+                });
+            assert_eq!(&state, tree.graph.node_weight(node).unwrap().state());
+        }
+
+        let edge = tree.find_edge(tree.root, &1).unwrap();
+        assert_eq!(tree.graph.edge_weight(edge).unwrap().action, 1);
+        assert!(tree.find_edge(tree.root, &7).is_none());
+    }
+
+    #[test]
+    fn reward_map_changes_what_is_backed_up() {
+        use crate::collections::testing::Race;
+
+        fn total(tree: &MonteCarloTree<Race>) -> (f64, u32) {
+            let root = &tree.graph[tree.root];
+            (root.reward().values().sum(), root.visits())
+        }
+
+        let mut tree = MonteCarloTree::seed_from_state(0, Race::new(6));
+        let mut doubled = MonteCarloTree::seed_from_state(0, Race::new(6))
+            .with_reward_map(|reward| reward.into_iter().map(|(p, r)| (p, 2.0 * r)).collect());
+        for _ in 0..100 {
+            tree.iterate(1.0).unwrap();
+            doubled.iterate(1.0).unwrap();
+        }
+
+        // every rollout pays the winner one point, or two once mapped
+        let (reward, visits) = total(&tree);
+        assert_eq!(reward, visits as f64);
+        let (reward, visits) = total(&doubled);
+        assert_eq!(reward, 2.0 * visits as f64);
+    }
 }
@@ -1,3 +1,5 @@
 mod seeded;
+mod symmetric;
 
 pub use seeded::Seeded;
+pub use symmetric::Symmetric;
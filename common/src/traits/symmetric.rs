@@ -0,0 +1,15 @@
+/// States that look the same after some rotation or reflection, such as a board game played on a
+/// square or mirrored grid. Solvers and transposition tables can store one entry per canonical
+/// state instead of one per orientation.
+pub trait Symmetric: Sized + PartialEq {
+    /// Every symmetric variant of the state, starting with the state itself. A state that is
+    /// symmetric itself shows up more than once.
+    fn symmetries(&self) -> Vec<Self>;
+
+    /// The variant that every symmetric state maps to.
+    fn canonical(&self) -> Self;
+
+    fn is_canonical(&self) -> bool {
+        self.canonical() == *self
+    }
+}
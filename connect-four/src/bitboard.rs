@@ -0,0 +1,318 @@
+use crate::{
+    error::{Error, Result},
+    Board, Checker,
+};
+use common::collections::{GameResult, GameState};
+use nanorand::Rng;
+use std::{collections::HashMap, fmt::Display};
+
+/// bits of a single column, bottom to top, not counting the empty row above it.
+const COLUMN: u64 = 0b111111;
+
+/// a Connect Four board packed into one mask per player. each column takes seven bits, the
+/// bottom six for the cells and an always-empty seventh row, so shifting a mask never carries
+/// a checker from the top of one column into the bottom of the next.
+/// |  5 | 12 | 19 | 26 | 33 | 40 | 47 |
+/// |  4 | 11 | 18 | 25 | 32 | 39 | 46 |
+/// |  3 | 10 | 17 | 24 | 31 | 38 | 45 |
+/// |  2 |  9 | 16 | 23 | 30 | 37 | 44 |
+/// |  1 |  8 | 15 | 22 | 29 | 36 | 43 |
+/// |  0 |  7 | 14 | 21 | 28 | 35 | 42 |
+/// ====================================
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitBoard {
+    red: u64,
+    yellow: u64,
+}
+
+impl BitBoard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mask(&self, player: Checker) -> u64 {
+        match player {
+            Checker::Red => self.red,
+            Checker::Yellow => self.yellow,
+        }
+    }
+
+    pub fn occupied(&self) -> u64 {
+        self.red | self.yellow
+    }
+
+    pub fn current_player(&self) -> Option<Checker> {
+        if self.red.count_ones() == self.yellow.count_ones() {
+            Some(Checker::Red)
+        } else {
+            Some(Checker::Yellow)
+        }
+    }
+
+    pub fn available_moves(&self) -> Vec<(Checker, usize)> {
+        let player = self.current_player().unwrap();
+        (0..7)
+            .filter(|&idx| self.height(idx) < 6)
+            .map(|idx| (player, idx))
+            .collect()
+    }
+
+    pub fn play_random(&self) -> Option<Self> {
+        let moves = self.available_moves();
+        if moves.is_empty() {
+            None
+        } else {
+            let idx = nanorand::tls_rng().generate_range(0..moves.len());
+            let (player, action) = moves[idx];
+            self.play_move(player, action)
+        }
+    }
+
+    /// The winner and their score, if either player has four in a row.
+    pub fn reward(&self) -> Option<(Checker, f32)> {
+        [Checker::Red, Checker::Yellow]
+            .into_iter()
+            .find(|&player| has_four(self.mask(player)))
+            .map(|player| (player, 1.0))
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.occupied().count_ones() == 42
+    }
+
+    pub fn play_move(&self, item: Checker, idx: usize) -> Option<Self> {
+        self.try_move(item, idx).ok()
+    }
+
+    /// Like `play_move`, but says why the move could not be played.
+    pub fn try_move(&self, item: Checker, idx: usize) -> Result<Self> {
+        if idx >= 7 {
+            return Err(Error::InvalidColumn(idx));
+        }
+        let height = self.height(idx);
+        if height == 6 {
+            return Err(Error::ColumnFull(idx));
+        }
+
+        let bit = 1 << (idx * 7 + height);
+        let mut out = *self;
+        match item {
+            Checker::Red => out.red |= bit,
+            Checker::Yellow => out.yellow |= bit,
+        }
+        Ok(out)
+    }
+
+    /// Takes the top checker back out of column `idx`, returning whose it was.
+    pub(crate) fn take_back(&self, idx: usize) -> Option<(Checker, Self)> {
+        if idx >= 7 || self.height(idx) == 0 {
+            return None;
+        }
+        let bit = 1 << (idx * 7 + self.height(idx) - 1);
+        let mut out = *self;
+        if self.red & bit != 0 {
+            out.red ^= bit;
+            Some((Checker::Red, out))
+        } else {
+            out.yellow ^= bit;
+            Some((Checker::Yellow, out))
+        }
+    }
+
+    /// whether every column is filled from the bottom up, with no gaps below a checker.
+    pub(crate) fn is_settled(&self) -> bool {
+        (0..7).all(|idx| {
+            let column = (self.occupied() >> (idx * 7)) & COLUMN;
+            column & (column + 1) == 0
+        })
+    }
+
+    /// the number of checkers in column `idx`.
+    fn height(&self, idx: usize) -> usize {
+        ((self.occupied() >> (idx * 7)) & COLUMN).count_ones() as usize
+    }
+}
+
+/// checks every direction at once: vertical (1), horizontal (7) and the two diagonals (6, 8).
+fn has_four(mask: u64) -> bool {
+    [1, 7, 6, 8].into_iter().any(|shift| {
+        let pairs = mask & (mask >> shift);
+        pairs & (pairs >> (2 * shift)) != 0
+    })
+}
+
+/// bit index in a `BitBoard` of cell `idx` in a `Board`, which counts rows from the top.
+fn bit_index(idx: usize) -> usize {
+    let (row, col) = (idx / 7, idx % 7);
+    col * 7 + (5 - row)
+}
+
+impl From<&Board<Option<Checker>>> for BitBoard {
+    fn from(board: &Board<Option<Checker>>) -> Self {
+        board
+            .cells
+            .iter()
+            .flatten()
+            .enumerate()
+            .fold(Self::default(), |mut out, (idx, cell)| {
+                let bit = 1 << bit_index(idx);
+                match cell {
+                    Some(Checker::Red) => out.red |= bit,
+                    Some(Checker::Yellow) => out.yellow |= bit,
+                    None => {}
+                }
+                out
+            })
+    }
+}
+
+impl From<Board<Option<Checker>>> for BitBoard {
+    fn from(board: Board<Option<Checker>>) -> Self {
+        Self::from(&board)
+    }
+}
+
+impl From<BitBoard> for Board<Option<Checker>> {
+    fn from(board: BitBoard) -> Self {
+        let mut out = Self::default();
+        for (idx, cell) in out.cells.iter_mut().flatten().enumerate() {
+            let bit = 1 << bit_index(idx);
+            *cell = if board.red & bit != 0 {
+                Some(Checker::Red)
+            } else if board.yellow & bit != 0 {
+                Some(Checker::Yellow)
+            } else {
+                None
+            };
+        }
+        out
+    }
+}
+
+impl Display for BitBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Board::from(*self))
+    }
+}
+
+/// The same rules as `Game`, for searches that need the faster board.
+impl GameState for BitBoard {
+    type Reward = f64;
+    type Action = usize;
+    type Player = Checker;
+    type ActionIter = std::vec::IntoIter<(Self::Player, Self::Action)>;
+    type Error = Error;
+
+    fn current_player(&self) -> Self::Player {
+        BitBoard::current_player(self).unwrap()
+    }
+
+    /// One point to the winner, or half a point each for a draw.
+    fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
+        match BitBoard::reward(self) {
+            Some((winner, score)) => HashMap::from([(winner, score as f64)]),
+            None if self.is_full() => HashMap::from([(Checker::Red, 0.5), (Checker::Yellow, 0.5)]),
+            None => HashMap::default(),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.is_full() || BitBoard::reward(self).is_some()
+    }
+
+    fn result(&self) -> Option<GameResult<Self::Player>> {
+        match BitBoard::reward(self) {
+            Some((winner, _)) => Some(GameResult::Win(winner)),
+            None => self.is_full().then_some(GameResult::Draw),
+        }
+    }
+
+    fn action_iter(&self) -> Self::ActionIter {
+        if self.is_complete() {
+            Vec::new().into_iter()
+        } else {
+            self.available_moves().into_iter()
+        }
+    }
+
+    fn step(self, player: &Self::Player, action: &Self::Action) -> Result<Self> {
+        if self.is_complete() {
+            return Err(Error::GameOver);
+        }
+        if *player != GameState::current_player(&self) {
+            return Err(Error::WrongPlayer(*player));
+        }
+        self.try_move(*player, *action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanorand::WyRand;
+
+    /// plays random games on both boards side by side, checking they agree after every move.
+    #[test]
+    fn matches_board() {
+        let mut rng = WyRand::new_seed(0);
+        for _ in 0..200 {
+            let mut board = Board::<Option<Checker>>::default();
+            let mut bits = BitBoard::new();
+            loop {
+                assert_eq!(BitBoard::from(&board), bits);
+                assert_eq!(Board::from(bits), board);
+                assert_eq!(bits.current_player(), board.current_player());
+                assert_eq!(bits.available_moves(), board.available_moves());
+                assert_eq!(bits.reward(), board.reward());
+                assert_eq!(bits.is_full(), board.is_full());
+                assert_eq!(bits.to_string(), board.to_string());
+
+                let moves = board.available_moves();
+                if moves.is_empty() || board.reward().is_some() {
+                    break;
+                }
+                let (player, action) = moves[rng.generate_range(0..moves.len())];
+                board = board.play_move(player, action).unwrap();
+                bits = bits.play_move(player, action).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn detects_fours_in_every_direction() {
+        for columns in [
+            vec![0, 0, 1, 1, 2, 2, 3],
+            vec![6, 6, 6, 6, 6, 6, 0, 1, 0, 1, 0, 1, 0],
+            vec![0, 1, 1, 2, 2, 3, 2, 3, 3, 6, 3],
+            vec![6, 5, 5, 4, 4, 3, 4, 3, 3, 0, 3],
+        ] {
+            let bits = columns.iter().try_fold(BitBoard::new(), |bits, &col| {
+                bits.play_move(bits.current_player().unwrap(), col)
+            });
+            assert_eq!(bits.unwrap().reward(), Some((Checker::Red, 1.0)));
+        }
+    }
+
+    #[test]
+    fn no_wrapping_between_columns() {
+        // red fills the top three cells of column 0 and the bottom cell of column 1, which are
+        // adjacent bits but not adjacent cells
+        let mut bits = BitBoard::new();
+        for (player, col) in [
+            (Checker::Yellow, 0),
+            (Checker::Yellow, 0),
+            (Checker::Yellow, 0),
+            (Checker::Red, 0),
+            (Checker::Red, 0),
+            (Checker::Red, 0),
+            (Checker::Red, 1),
+        ] {
+            bits = bits.play_move(player, col).unwrap();
+        }
+        assert_eq!(bits.reward(), None);
+        assert!(matches!(
+            bits.try_move(Checker::Yellow, 0),
+            Err(Error::ColumnFull(0))
+        ));
+    }
+}
@@ -1,103 +1,195 @@
+use crate::error::{Error, Result};
+use common::{
+    collections::{GameResult, GameState},
+    traits::Symmetric,
+};
 use nanorand::Rng;
-use std::fmt::{Debug, Display};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
 
-#[derive(Debug, Clone)]
-pub struct Game {
-    board: Board<Option<Checker>>,
+/// A game of Connect Four, or of connect-`K` on a `W` by `H` board. Defaults to the standard
+/// game.
+///
+/// Games compare and hash by their board alone, which also decides the player to move. The last
+/// move is left out, so transpositions compare equal.
+#[derive(Debug, Default, Clone)]
+pub struct Game<const W: usize = 7, const H: usize = 6, const K: usize = 4> {
+    board: Board<Option<Checker>, W, H, K>,
     previous_move: Option<(Checker, usize)>,
-    score: (Checker, f32),
-    visits: u32,
 }
 
-impl Default for Game {
-    fn default() -> Self {
+impl<const W: usize, const H: usize, const K: usize> Game<W, H, K> {
+    pub fn board(&self) -> &Board<Option<Checker>, W, H, K> {
+        &self.board
+    }
+
+    pub fn previous_move(&self) -> Option<&(Checker, usize)> {
+        self.previous_move.as_ref()
+    }
+
+    /// the player with `K` in a row. only a line through the last checker dropped can be new, so
+    /// that is all that gets checked when there is one.
+    fn winner(&self) -> Option<Checker> {
+        match self.previous_move {
+            Some((player, col)) => {
+                let row = H - self.board.height(col);
+                self.board.in_line(row, col).then_some(player)
+            }
+            None => self.board.reward().map(|(winner, _)| winner),
+        }
+    }
+
+    /// Drops the current player's checker into column `action`.
+    pub fn update(self, action: usize) -> Result<Self> {
+        if self.is_complete() {
+            return Err(Error::GameOver);
+        }
+        let player = self.current_player();
+        let board = self.board.try_move(player, action)?;
+        Ok(Self {
+            board,
+            previous_move: Some((player, action)),
+        })
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> From<Board<Option<Checker>, W, H, K>>
+    for Game<W, H, K>
+{
+    fn from(board: Board<Option<Checker>, W, H, K>) -> Self {
         Self {
-            board: Board::<Option<Checker>>::default(),
+            board,
             previous_move: None,
-            score: (Checker::default(), 0.0),
-            visits: 0,
         }
     }
 }
 
-impl Game {
-    pub fn update(self, action: usize) -> Self {
-        let new_board = self
-            .board
-            .play_move(self.board.current_player().unwrap(), action)
-            .unwrap();
-        Self {
-            board: new_board,
-            previous_move: Some((self.board.current_player().unwrap(), action)),
-            score: self.score,
-            visits: self.visits,
+impl<const W: usize, const H: usize, const K: usize> PartialEq for Game<W, H, K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> Eq for Game<W, H, K> {}
+
+impl<const W: usize, const H: usize, const K: usize> Hash for Game<W, H, K> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.board.hash(state);
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> Display for Game<W, H, K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.board)
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> GameState for Game<W, H, K> {
+    type Reward = f64;
+    type Action = usize;
+    type Player = Checker;
+    type ActionIter = std::vec::IntoIter<(Self::Player, Self::Action)>;
+    type Error = Error;
+
+    fn current_player(&self) -> Self::Player {
+        self.board.current_player().unwrap()
+    }
+
+    /// One point to the winner, or half a point each for a draw.
+    fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
+        match self.winner() {
+            Some(winner) => HashMap::from([(winner, 1.0)]),
+            None if self.board.is_full() => {
+                HashMap::from([(Checker::Red, 0.5), (Checker::Yellow, 0.5)])
+            }
+            None => HashMap::default(),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.board.is_full() || self.winner().is_some()
+    }
+
+    fn result(&self) -> Option<GameResult<Self::Player>> {
+        match self.winner() {
+            Some(winner) => Some(GameResult::Win(winner)),
+            None => self.board.is_full().then_some(GameResult::Draw),
+        }
+    }
+
+    fn action_iter(&self) -> Self::ActionIter {
+        if self.is_complete() {
+            Vec::new().into_iter()
+        } else {
+            self.board.available_moves().into_iter()
+        }
+    }
+
+    fn step(self, player: &Self::Player, action: &Self::Action) -> Result<Self> {
+        if *player != self.current_player() {
+            return Err(Error::WrongPlayer(*player));
         }
+        self.update(*action)
     }
 }
 
-// impl Game {
-//     fn players(&self) -> Vec<Self::Player> {
-//         vec![Checker::Red, Checker::Yellow]
-//     }
-//
-//     fn actions(&self) -> Vec<(Self::Player, Self::Action)> {
-//         self.board.available_moves()
-//     }
-//
-//     fn step(&self, player: Self::Player, action: Self::Action) -> Self {
-//         self.clone().update(action.clone())
-//     }
-//
-//     fn reward(&self) -> Option<GameScore<Self::Player, f32>> {
-//         self.board.reward()
-//     }
-//
-//     fn score(&self) -> GameScore<Self::Player, f32> {
-//         self.score().clone()
-//     }
-//
-//     fn set_score(&mut self, score: GameScore<Self::Player, f32>) {
-//         self.score = score;
-//     }
-//
-//     fn visits(&self) -> u32 {
-//         self.visits
-//     }
-//
-//     fn set_visits(&mut self, visits: u32) {
-//         self.visits = visits;
-//     }
-//
-//     fn current_player(&self) -> Self::Player {
-//         self.board.current_player().unwrap()
-//     }
-//
-//     fn previous_move(&self) -> Option<&(Self::Player, Self::Action)> {
-//         self.previous_move.as_ref()
-//     }
-// }
-
-/// the Connect Four board. contains cells numbered 0 through 41.
-/// |  0 |  1 |  2 |  3 |  4 |  5 |  6 |
-/// |  7 |  8 |  9 | 10 | 11 | 12 | 13 |
-/// | 14 | 15 | 16 | 17 | 18 | 19 | 20 |
-/// | 21 | 22 | 23 | 24 | 25 | 26 | 27 |
-/// | 28 | 29 | 30 | 31 | 32 | 33 | 34 |
-/// | 35 | 36 | 37 | 38 | 39 | 40 | 41 |
-/// ====================================
-#[derive(Debug, Clone)]
-pub struct Board<T> {
-    cells: [T; 42],
+/// the Connect Four board, `W` columns wide and `H` rows high, won by lining up `K` checkers.
+/// rows are numbered from the top and columns from the left, so the standard board is
+/// |  0,0 |  0,1 |  0,2 |  0,3 |  0,4 |  0,5 |  0,6 |
+/// |  1,0 |  1,1 |  1,2 |  1,3 |  1,4 |  1,5 |  1,6 |
+/// |  2,0 |  2,1 |  2,2 |  2,3 |  2,4 |  2,5 |  2,6 |
+/// |  3,0 |  3,1 |  3,2 |  3,3 |  3,4 |  3,5 |  3,6 |
+/// |  4,0 |  4,1 |  4,2 |  4,3 |  4,4 |  4,5 |  4,6 |
+/// |  5,0 |  5,1 |  5,2 |  5,3 |  5,4 |  5,5 |  5,6 |
+/// ==================================================
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Board<T, const W: usize = 7, const H: usize = 6, const K: usize = 4> {
+    pub(crate) cells: [[T; W]; H],
 }
 
-impl Board<Option<Checker>> {
+impl<T, const W: usize, const H: usize, const K: usize> Board<T, W, H, K> {
+    /// The cell at `row`, counting from the top, and `col`.
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        self.cells.get(row)?.get(col)
+    }
+
+    /// Every run of `K` cells a player can win with, as `(row, col)` pairs: rows, columns and
+    /// both diagonals.
+    pub fn winning_lines() -> impl Iterator<Item = [(usize, usize); K]> {
+        let directions = [(0, 1), (1, 0), (1, 1), (1, -1)];
+        directions.into_iter().flat_map(|(dr, dc): (usize, isize)| {
+            (0..H).flat_map(move |row| {
+                (0..W).filter_map(move |col| {
+                    let end_row = row + dr * K.checked_sub(1)?;
+                    let end_col = col as isize + dc * (K as isize - 1);
+                    if end_row >= H || end_col < 0 || end_col >= W as isize {
+                        return None;
+                    }
+                    Some(std::array::from_fn(|i| {
+                        (row + dr * i, (col as isize + dc * i as isize) as usize)
+                    }))
+                })
+            })
+        })
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> Board<Option<Checker>, W, H, K> {
     pub fn current_player(&self) -> Option<Checker> {
         // who has fewer pieces?
-        let totals = self.cells.iter().fold((0, 0), |total, cell| match cell {
-            Some(Checker::Red) => (total.0 + 1, total.1),
-            Some(Checker::Yellow) => (total.0, total.1 + 1),
-            None => total,
-        });
+        let totals = self
+            .cells
+            .iter()
+            .flatten()
+            .fold((0, 0), |total, cell| match cell {
+                Some(Checker::Red) => (total.0 + 1, total.1),
+                Some(Checker::Yellow) => (total.0, total.1 + 1),
+                None => total,
+            });
 
         if totals.0 == totals.1 {
             Some(Checker::Red)
@@ -107,8 +199,8 @@ impl Board<Option<Checker>> {
     }
 
     pub fn available_moves(&self) -> Vec<(Checker, usize)> {
-        (0..7)
-            .filter(|&idx| self.cells[idx].is_none())
+        (0..W)
+            .filter(|&idx| self.next_available(idx).is_some())
             .map(|idx| (self.current_player().unwrap(), idx))
             .collect()
     }
@@ -119,33 +211,69 @@ impl Board<Option<Checker>> {
             None
         } else {
             let idx = nanorand::tls_rng().generate_range(0..moves.len());
-            let (player, action) = moves[idx].clone();
+            let (player, action) = moves[idx];
             self.play_move(player, action)
         }
     }
 
+    /// The winner and their score, if either player has `K` in a row.
     pub fn reward(&self) -> Option<(Checker, f32)> {
-        FOURS.into_iter().find_map(|indices| {
-            let first = self.cells[indices[0]].as_ref()?;
-            if indices
-                .into_iter()
-                .map(|idx| self.cells[idx].as_ref())
-                .all(|cell| cell == Some(first))
-            {
-                let mut score = (Checker::default(), 0.0);
-                match first {
-                    Checker::Red => (Checker::Red, 1.0),
-                    Checker::Yellow => (Checker::Yellow, 1.0),
-                };
-                Some(score)
-            } else {
-                None
-            }
-        })
+        let (row, col) = self.cells_in_lines().next()?;
+        Some((self.cells[row][col]?, 1.0))
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.cells.iter().flatten().all(Option::is_some)
+    }
+
+    /// Whether `checker` has `K` in a row anywhere on the board.
+    pub fn has_line(&self, checker: Checker) -> bool {
+        self.cells_in_lines()
+            .any(|(row, col)| self.cells[row][col] == Some(checker))
+    }
+
+    /// Whether the checker at `row`, `col` is part of `K` in a row of its own color.
+    pub fn in_line(&self, row: usize, col: usize) -> bool {
+        let Some(checker) = self.get(row, col).copied().flatten() else {
+            return false;
+        };
+        [(0, 1), (1, 0), (1, 1), (1, -1)]
+            .into_iter()
+            .any(|step| self.run_length(row, col, checker, step) >= K)
+    }
+
+    /// every occupied cell that is part of a line, row by row from the top.
+    fn cells_in_lines(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..H)
+            .flat_map(|row| (0..W).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.in_line(row, col))
+    }
+
+    /// the number of `checker`s in an unbroken run through `row`, `col`, stepping by
+    /// `(dr, dc)` both ways.
+    fn run_length(
+        &self,
+        row: usize,
+        col: usize,
+        checker: Checker,
+        (dr, dc): (isize, isize),
+    ) -> usize {
+        let at = |steps: isize| {
+            let row = usize::try_from(row as isize + dr * steps).ok()?;
+            let col = usize::try_from(col as isize + dc * steps).ok()?;
+            self.get(row, col).copied().flatten()
+        };
+        let forward = (1..)
+            .take_while(|&steps| at(steps) == Some(checker))
+            .count();
+        let backward = (1..)
+            .take_while(|&steps| at(-steps) == Some(checker))
+            .count();
+        1 + forward + backward
     }
 }
 
-impl<T: Debug + Clone + PartialEq> Board<Option<T>> {
+impl<T: Clone, const W: usize, const H: usize, const K: usize> Board<Option<T>, W, H, K> {
     pub fn from_moves(moves: &[(T, usize)]) -> Option<Self> {
         let mut board = Self::default();
 
@@ -156,33 +284,64 @@ impl<T: Debug + Clone + PartialEq> Board<Option<T>> {
     }
 
     pub fn play_move(&self, item: T, idx: usize) -> Option<Self> {
-        self.next_available(idx).map(|idx| {
-            let mut cells = self.cells.clone();
-            cells[idx] = Some(item);
-            Self { cells }
-        })
+        self.try_move(item, idx).ok()
+    }
+
+    /// Like `play_move`, but says why the move could not be played.
+    pub fn try_move(&self, item: T, idx: usize) -> Result<Self> {
+        if idx >= W {
+            return Err(Error::InvalidColumn(idx));
+        }
+        let row = self.next_available(idx).ok_or(Error::ColumnFull(idx))?;
+        let mut cells = self.cells.clone();
+        cells[row][idx] = Some(item);
+        Ok(Self { cells })
     }
 
+    /// Removes the bottom checker of column `idx`, letting the rest of the column fall one row.
+    pub fn try_pop(&self, idx: usize) -> Result<(T, Self)> {
+        if idx >= W {
+            return Err(Error::InvalidColumn(idx));
+        }
+        let mut cells = self.cells.clone();
+        let item = cells[H - 1][idx].take().ok_or(Error::CannotPop(idx))?;
+        for row in (1..H).rev() {
+            cells[row][idx] = cells[row - 1][idx].take();
+        }
+        Ok((item, Self { cells }))
+    }
+
+    /// The number of checkers in column `idx`.
+    pub fn height(&self, idx: usize) -> usize {
+        (0..H)
+            .filter(|&row| self.get(row, idx).is_some_and(Option::is_some))
+            .count()
+    }
+
+    /// the lowest empty row in column `idx`.
     fn next_available(&self, idx: usize) -> Option<usize> {
-        let end = idx + 7 * 5 + 1;
-        (idx..end)
-            .step_by(7)
-            .take(6)
-            .rev()
-            .find(|&idx| self.cells[idx].is_none())
+        if idx >= W {
+            return None;
+        }
+        (0..H).rev().find(|&row| self.cells[row][idx].is_none())
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Checker {
+    // red plays first
+    #[default]
     Red,
     Yellow,
 }
 
-impl Default for Checker {
-    // red plays first
-    fn default() -> Self {
-        Self::Red
+impl Checker {
+    /// The other player's checker.
+    pub fn opponent(self) -> Self {
+        match self {
+            Self::Red => Self::Yellow,
+            Self::Yellow => Self::Red,
+        }
     }
 }
 
@@ -195,13 +354,14 @@ impl std::fmt::Display for Checker {
     }
 }
 
-impl<T: Display> Display for Board<Option<T>> {
+impl<T: Display, const W: usize, const H: usize, const K: usize> Display
+    for Board<Option<T>, W, H, K>
+{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in 0..6 {
+        for row in self.cells.iter() {
             write!(f, "|")?;
-            for col in 0..7 {
-                let idx = row * 7 + col;
-                let s = self.cells[idx]
+            for cell in row.iter() {
+                let s = cell
                     .as_ref()
                     .map(|cell| cell.to_string())
                     .unwrap_or(" ".to_string());
@@ -209,90 +369,343 @@ impl<T: Display> Display for Board<Option<T>> {
             }
             writeln!(f)?;
         }
-        writeln!(f, "=============================")?;
+        writeln!(f, "{}", "=".repeat(4 * W + 1))?;
         Ok(())
     }
 }
 
-impl<T: Debug + Default> Default for Board<T> {
+/// Reads back the grid printed by `Display`, with `x` for red and `o` for yellow. The board
+/// must be one a game could reach: no floating checkers, and red either level with yellow or
+/// one checker ahead.
+impl<const W: usize, const H: usize, const K: usize> FromStr for Board<Option<Checker>, W, H, K> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lines = s.lines().collect::<Vec<_>>();
+        if lines.len() != H + 1 {
+            return Err(Error::MalformedLine(lines.len().min(H + 1)));
+        }
+        if lines[H] != "=".repeat(4 * W + 1) {
+            return Err(Error::MalformedLine(H));
+        }
+
+        let mut board = Self::default();
+        for (row, line) in lines[..H].iter().enumerate() {
+            let chars = line.chars().collect::<Vec<_>>();
+            if chars.len() != 4 * W + 1 || chars[0] != '|' {
+                return Err(Error::MalformedLine(row));
+            }
+            for (col, cell) in chars[1..].chunks(4).enumerate() {
+                let [' ', c, ' ', '|'] = cell else {
+                    return Err(Error::MalformedLine(row));
+                };
+                board.cells[row][col] = match c {
+                    'x' => Some(Checker::Red),
+                    'o' => Some(Checker::Yellow),
+                    ' ' => None,
+                    c => return Err(Error::InvalidCharacter(*c)),
+                };
+            }
+        }
+
+        let floating = (0..H.saturating_sub(1))
+            .flat_map(|row| (0..W).map(move |col| (row, col)))
+            .find(|&(row, col)| {
+                board.cells[row][col].is_some() && board.cells[row + 1][col].is_none()
+            });
+        if let Some((row, col)) = floating {
+            return Err(Error::FloatingChecker(row * W + col));
+        }
+        let count = |checker| {
+            board
+                .cells
+                .iter()
+                .flatten()
+                .filter(|&cell| *cell == Some(checker))
+                .count()
+        };
+        let (red, yellow) = (count(Checker::Red), count(Checker::Yellow));
+        if red != yellow && red != yellow + 1 {
+            return Err(Error::CheckerCount(red, yellow));
+        }
+        Ok(board)
+    }
+}
+
+impl<T: Clone, const W: usize, const H: usize, const K: usize> Board<T, W, H, K> {
+    /// The board reflected left to right.
+    pub fn mirrored(&self) -> Self {
+        let mut cells = self.cells.clone();
+        cells.iter_mut().for_each(|row| row.reverse());
+        Self { cells }
+    }
+}
+
+/// A board and its mirror image play the same. The canonical one of the two is whichever comes
+/// first reading the cells row by row from the top, empty before red before yellow.
+impl<const W: usize, const H: usize, const K: usize> Symmetric for Board<Option<Checker>, W, H, K> {
+    fn symmetries(&self) -> Vec<Self> {
+        vec![self.clone(), self.mirrored()]
+    }
+
+    fn canonical(&self) -> Self {
+        let key = |board: &Self| {
+            board
+                .cells
+                .iter()
+                .flatten()
+                .map(|cell| cell.map_or(0, |checker| checker as u8 + 1))
+                .collect::<Vec<_>>()
+        };
+        let mirrored = self.mirrored();
+        if key(&mirrored) < key(self) {
+            mirrored
+        } else {
+            self.clone()
+        }
+    }
+}
+
+impl<T: Default, const W: usize, const H: usize, const K: usize> Default for Board<T, W, H, K> {
     fn default() -> Self {
-        let cells = std::iter::repeat_with(T::default)
-            .take(42)
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap();
+        let cells = std::array::from_fn(|_| std::array::from_fn(|_| T::default()));
         Self { cells }
     }
 }
 
-const FOURS: [[usize; 4]; 69] = [
-    [0, 1, 2, 3],
-    [1, 2, 3, 4],
-    [2, 3, 4, 5],
-    [3, 4, 5, 6],
-    [7, 8, 9, 10],
-    [8, 9, 10, 11],
-    [9, 10, 11, 12],
-    [10, 11, 12, 13],
-    [14, 15, 16, 17],
-    [15, 16, 17, 18],
-    [16, 17, 18, 19],
-    [17, 18, 19, 20],
-    [21, 22, 23, 24],
-    [22, 23, 24, 25],
-    [23, 24, 25, 26],
-    [24, 25, 26, 27],
-    [28, 29, 30, 31],
-    [29, 30, 31, 32],
-    [30, 31, 32, 33],
-    [31, 32, 33, 34],
-    [35, 36, 37, 38],
-    [36, 37, 38, 39],
-    [37, 38, 39, 40],
-    [38, 39, 40, 41],
-    [0, 7, 14, 21],
-    [7, 14, 21, 28],
-    [14, 21, 28, 35],
-    [1, 8, 15, 22],
-    [8, 15, 22, 29],
-    [15, 22, 29, 36],
-    [2, 9, 16, 23],
-    [9, 16, 23, 30],
-    [16, 23, 30, 37],
-    [3, 10, 17, 24],
-    [10, 17, 24, 31],
-    [17, 24, 31, 38],
-    [4, 11, 18, 25],
-    [11, 18, 25, 32],
-    [18, 25, 32, 39],
-    [5, 12, 19, 26],
-    [12, 19, 26, 33],
-    [19, 26, 33, 40],
-    [6, 13, 20, 27],
-    [13, 20, 27, 34],
-    [20, 27, 34, 41],
-    [0, 8, 16, 24],
-    [1, 9, 17, 25],
-    [2, 10, 18, 26],
-    [3, 11, 19, 27],
-    [7, 15, 23, 31],
-    [8, 16, 24, 32],
-    [9, 17, 25, 33],
-    [10, 18, 26, 34],
-    [14, 22, 30, 38],
-    [15, 23, 31, 39],
-    [16, 24, 32, 40],
-    [17, 25, 33, 41],
-    [3, 9, 15, 21],
-    [4, 10, 16, 22],
-    [5, 11, 17, 23],
-    [6, 12, 18, 24],
-    [10, 16, 22, 28],
-    [11, 17, 23, 29],
-    [12, 18, 24, 30],
-    [13, 19, 25, 31],
-    [17, 23, 29, 35],
-    [18, 24, 30, 36],
-    [19, 25, 31, 37],
-    [20, 26, 32, 38],
-];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::collections::{MctsNode, MonteCarloTree, MoveSelection, SearchBudget, Ucb1};
+
+    fn play(columns: &[usize]) -> Result<Game> {
+        columns
+            .iter()
+            .try_fold(Game::default(), |game, &col| game.update(col))
+    }
+
+    #[test]
+    fn detects_wins() {
+        // horizontal, vertical and both diagonals
+        for (columns, winner) in [
+            (vec![0, 0, 1, 1, 2, 2, 3], Checker::Red),
+            (vec![6, 0, 1, 0, 2, 0, 3, 0], Checker::Yellow),
+            (vec![0, 1, 1, 2, 2, 3, 2, 3, 3, 6, 3], Checker::Red),
+            (vec![0, 6, 5, 5, 4, 4, 3, 4, 3, 3, 1, 3], Checker::Yellow),
+        ] {
+            let game = play(&columns).unwrap();
+            assert!(game.is_complete(), "{}", game);
+            assert_eq!(game.reward(), HashMap::from([(winner, 1.0)]));
+            assert!(game.action_iter().next().is_none());
+            // without a last move, the whole board is checked
+            let board = Game::from(game.board().clone());
+            assert_eq!(board.reward(), game.reward());
+        }
+    }
+
+    #[test]
+    fn detects_draws() {
+        let columns = [
+            4, 3, 6, 0, 1, 4, 5, 5, 1, 1, 5, 0, 1, 6, 0, 1, 5, 5, 1, 0, 4, 6, 3, 2, 6, 6, 0, 4, 6,
+            5, 2, 0, 4, 2, 4, 2, 2, 2, 3, 3, 3, 3,
+        ];
+        let mut game: Game = Game::default();
+        for (turn, col) in columns.into_iter().enumerate() {
+            assert!(!game.is_complete(), "turn {}\n{}", turn, game);
+            game = game.update(col).unwrap();
+        }
+        assert!(game.board().is_full());
+        assert!(game.is_complete());
+        assert_eq!(
+            game.reward(),
+            HashMap::from([(Checker::Red, 0.5), (Checker::Yellow, 0.5)])
+        );
+    }
+
+    #[test]
+    fn rejects_illegal_moves() {
+        let game = play(&[0, 0, 0, 0, 0, 0]).unwrap();
+        assert!(matches!(game.clone().update(0), Err(Error::ColumnFull(0))));
+        assert!(matches!(
+            game.clone().update(7),
+            Err(Error::InvalidColumn(7))
+        ));
+        assert!(matches!(
+            game.step(&Checker::Yellow, &1),
+            Err(Error::WrongPlayer(Checker::Yellow))
+        ));
+
+        let won = play(&[0, 1, 0, 1, 0, 1, 0]).unwrap();
+        assert!(matches!(won.update(2), Err(Error::GameOver)));
+    }
+
+    #[test]
+    fn parses_printed_boards() {
+        for columns in [vec![], vec![3, 3, 4, 2], vec![0, 1, 0, 1, 0, 1, 0]] {
+            let board = play(&columns).unwrap().board().clone();
+            assert_eq!(board.to_string().parse::<Board<_>>().unwrap(), board);
+        }
+
+        let board = play(&[3, 3, 4]).unwrap().board().to_string();
+        let floating = [
+            "|   |   |   |   |   |   |   |",
+            "|   |   |   |   |   |   |   |",
+            "|   |   |   |   |   |   |   |",
+            "|   |   |   | x |   |   |   |",
+            "|   |   |   |   |   |   |   |",
+            "|   |   |   | x | o |   |   |",
+            "=============================",
+        ]
+        .join("\n");
+        assert!(matches!(
+            floating.parse::<Board<_>>(),
+            Err(Error::FloatingChecker(24))
+        ));
+        assert!(matches!(
+            board.replace('o', "x").parse::<Board<_>>(),
+            Err(Error::CheckerCount(3, 0))
+        ));
+        assert!(matches!(
+            board.replacen(" x ", " z ", 1).parse::<Board<_>>(),
+            Err(Error::InvalidCharacter('z'))
+        ));
+        assert!(matches!(
+            board.replacen("|", "", 1).parse::<Board<_>>(),
+            Err(Error::MalformedLine(_))
+        ));
+        assert!(matches!(
+            board
+                .lines()
+                .skip(1)
+                .collect::<Vec<_>>()
+                .join("\n")
+                .parse::<Board<_>>(),
+            Err(Error::MalformedLine(_))
+        ));
+    }
+
+    #[test]
+    fn generates_winning_lines() {
+        assert_eq!(Board::<Option<Checker>>::winning_lines().count(), 69);
+        assert_eq!(Board::<Option<Checker>, 8, 7>::winning_lines().count(), 107);
+        assert_eq!(
+            Board::<Option<Checker>, 9, 7, 5>::winning_lines().count(),
+            92
+        );
+
+        let lines = Board::<Option<Checker>>::winning_lines().collect::<Vec<_>>();
+        assert!(lines.contains(&[(2, 3), (3, 2), (4, 1), (5, 0)]));
+        assert!(lines.contains(&[(2, 6), (3, 5), (4, 4), (5, 3)]));
+        assert!(lines.iter().flatten().all(|&(row, col)| row < 6 && col < 7));
+    }
+
+    #[test]
+    fn line_checks_agree_with_winning_lines() {
+        for _ in 0..200 {
+            let mut board = Board::<Option<Checker>>::default();
+            while let Some(next) = board.play_random() {
+                board = next;
+            }
+            let complete = |line: &[(usize, usize); 4], checker| {
+                line.iter()
+                    .all(|&(row, col)| board.cells[row][col] == Some(checker))
+            };
+            for checker in [Checker::Red, Checker::Yellow] {
+                let expected =
+                    Board::<Option<Checker>>::winning_lines().any(|line| complete(&line, checker));
+                assert_eq!(board.has_line(checker), expected, "\n{}", board);
+            }
+            for (row, col) in (0..6).flat_map(|row| (0..7).map(move |col| (row, col))) {
+                let expected = Board::<Option<Checker>>::winning_lines().any(|line| {
+                    line.contains(&(row, col))
+                        && board.cells[row][col].is_some_and(|checker| complete(&line, checker))
+                });
+                assert_eq!(board.in_line(row, col), expected, "\n{}", board);
+            }
+        }
+    }
+
+    #[test]
+    fn plays_other_sizes() {
+        // four in a row no longer wins when five are needed
+        let game = [0, 0, 1, 1, 2, 2, 3, 3]
+            .into_iter()
+            .try_fold(Game::<9, 7, 5>::default(), |game, col| game.update(col))
+            .unwrap();
+        assert!(!game.is_complete());
+        let game = game.update(4).unwrap();
+        assert_eq!(game.reward(), HashMap::from([(Checker::Red, 1.0)]));
+
+        // a column on the 8x7 board holds seven checkers
+        let game = [7; 7]
+            .into_iter()
+            .try_fold(Game::<8, 7>::default(), |game, col| game.update(col))
+            .unwrap();
+        assert_eq!(game.board().get(0, 7), Some(&Some(Checker::Red)));
+        assert!(matches!(game.clone().update(7), Err(Error::ColumnFull(7))));
+        assert!(matches!(
+            game.clone().update(8),
+            Err(Error::InvalidColumn(8))
+        ));
+        assert_eq!(game.board().to_string().lines().count(), 8);
+        assert_eq!(
+            game.board().to_string().parse::<Board<_, 8, 7>>().unwrap(),
+            game.board().clone()
+        );
+    }
+
+    #[test]
+    fn search_takes_the_win() {
+        let game = play(&[0, 6, 1, 6, 2, 5]).unwrap();
+        let mut tree = MonteCarloTree::seed_from_state(0, game);
+        tree.search(&Ucb1::default(), &SearchBudget::new().iterations(2_000));
+        let best = tree.best_action(MoveSelection::RobustChild).unwrap();
+        assert_eq!(best.action, 3);
+    }
+
+    #[test]
+    fn mirrors_boards() {
+        let board = play(&[0, 3, 1]).unwrap().board().clone();
+        let mirrored = play(&[6, 3, 5]).unwrap().board().clone();
+        assert_eq!(board.mirrored(), mirrored);
+        assert_eq!(board.symmetries(), vec![board.clone(), mirrored.clone()]);
+        assert_eq!(board.canonical(), mirrored.canonical());
+        assert!(board.canonical().is_canonical());
+        assert_eq!(board.mirrored().reward(), board.reward());
+
+        // the center column maps onto itself
+        let center = play(&[3, 3]).unwrap().board().clone();
+        assert_eq!(center.mirrored(), center);
+        assert!(center.is_canonical());
+
+        let board = Board::<Option<Checker>, 8, 7, 5>::default()
+            .play_move(Checker::Red, 0)
+            .unwrap();
+        assert_eq!(board.mirrored().get(6, 7), Some(&Some(Checker::Red)));
+    }
+
+    #[test]
+    fn transpositions_share_a_node() {
+        let game = play(&[0, 1, 2]).unwrap();
+        let other = play(&[2, 1, 0]).unwrap();
+        assert_eq!(game, other);
+        let hash = |game: &Game| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            game.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&game), hash(&other));
+
+        let mut tree = MonteCarloTree::<Game>::seed_from_u64(0).with_transpositions();
+        let mut reach = |columns: [usize; 3]| {
+            columns.into_iter().fold(tree.root, |node, col| {
+                let _ = tree.expand_from(node);
+                let edge = tree.find_edge(node, &col).unwrap();
+                tree.graph.edge_endpoints(edge).unwrap().1
+            })
+        };
+        let node = reach([0, 1, 2]);
+        assert_eq!(reach([2, 1, 0]), node);
+        assert_eq!(tree.graph[node].state(), &game);
+    }
+}
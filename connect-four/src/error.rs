@@ -0,0 +1,75 @@
+use crate::Checker;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidColumn(usize),
+    ColumnFull(usize),
+    WrongPlayer(Checker),
+    GameOver,
+    InvalidCharacter(char),
+    /// a move sequence broke the rules at the move with this index.
+    IllegalMove(usize, Box<Error>),
+    /// a character of written notation, at this zero-based index, that isn't a column from 1
+    /// to 7. the notation only covers the standard 7 by 6 board.
+    NotAColumn(char, usize),
+    /// a column of written notation, at this zero-based index, that can't be played.
+    IllegalNotation(char, usize, Box<Error>),
+    /// a board that no legal sequence of moves leads to.
+    Unreachable,
+    /// a line of a printed board, counting from zero, that doesn't match the format.
+    MalformedLine(usize),
+    /// a checker with an empty cell beneath it.
+    FloatingChecker(usize),
+    /// red and yellow checker counts that can't come from alternating turns.
+    CheckerCount(usize, usize),
+    /// the bottom of this column doesn't hold one of the mover's checkers.
+    CannotPop(usize),
+    /// the rules don't allow a drop into this column right now.
+    CannotDrop(usize),
+    /// passing is only allowed when no other move is.
+    CannotPass,
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidColumn(col) => write!(f, "column index {} is out of range", col),
+            Self::ColumnFull(col) => write!(f, "column {} is full", col),
+            Self::WrongPlayer(player) => write!(f, "it is not {:?}'s turn", player),
+            Self::GameOver => write!(f, "the game is already over"),
+            Self::InvalidCharacter(c) => write!(f, "unexpected character {:?}", c),
+            Self::IllegalMove(idx, error) => write!(f, "move {} is illegal: {}", idx + 1, error),
+            Self::NotAColumn(c, idx) => {
+                write!(
+                    f,
+                    "{:?} at position {} is not a column from 1 to 7 of the standard board",
+                    c,
+                    idx + 1
+                )
+            }
+            // the character already names the column, and the inner error would number it
+            // from zero
+            Self::IllegalNotation(c, idx, error) => match **error {
+                Self::ColumnFull(_) => {
+                    write!(f, "{:?} at position {} is a full column", c, idx + 1)
+                }
+                _ => write!(f, "{:?} at position {} is illegal: {}", c, idx + 1, error),
+            },
+            Self::Unreachable => write!(f, "no legal sequence of moves reaches this board"),
+            Self::MalformedLine(line) => write!(f, "line {} is not a board row", line + 1),
+            Self::FloatingChecker(idx) => write!(f, "the checker at cell {} is floating", idx),
+            Self::CheckerCount(red, yellow) => write!(
+                f,
+                "{} red and {} yellow checkers can't come from alternating turns",
+                red, yellow
+            ),
+            Self::CannotPop(col) => write!(f, "cannot pop from column {}", col),
+            Self::CannotDrop(col) => write!(f, "cannot drop into column {}", col),
+            Self::CannotPass => write!(f, "cannot pass while there is a move to make"),
+        }
+    }
+}
@@ -1,3 +1,14 @@
+mod bitboard;
 mod board;
+pub mod error;
+mod notation;
+mod popout;
+mod popten;
+mod solver;
 
+pub use bitboard::BitBoard;
 pub use board::{Board, Checker, Game};
+pub use notation::MoveSequence;
+pub use popout::{PopOut, PopOutAction};
+pub use popten::{PopTen, PopTenAction, PopTenPhase};
+pub use solver::Solver;
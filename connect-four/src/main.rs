@@ -1 +1,26 @@
-fn main() {}
+use common::collections::{GameState, MonteCarloTree, MoveSelection, SearchBudget, Ucb1};
+use connect_four::{BitBoard, Game, Solver};
+
+fn main() {
+    let mut game: Game = Game::default();
+    let mut tree = MonteCarloTree::<Game>::new();
+    let mut solver = Solver::new();
+    let budget = SearchBudget::new().iterations(20_000);
+
+    while !game.is_complete() {
+        tree.search(&Ucb1::default(), &budget);
+        let best = tree.best_action(MoveSelection::RobustChild).unwrap();
+        println!("{} plays column {}", best.player, best.action);
+
+        // openings take the solver too long, so only check the search from the middle game on
+        if BitBoard::from(game.board()).occupied().count_ones() >= 12 {
+            println!("solver scores: {:?}", solver.analyze(game.board()));
+        }
+
+        game = game.step(&best.player, &best.action).unwrap();
+        tree.advance(&best.action).unwrap();
+        println!("{}", game);
+    }
+
+    println!("{:?}", game.reward());
+}
@@ -0,0 +1,303 @@
+use crate::{
+    error::{Error, Result},
+    BitBoard, Board, Checker, Game,
+};
+use std::{collections::HashSet, fmt::Display, str::FromStr};
+
+/// A game written as the columns played in order, numbered 1 through 7 from the left, the usual
+/// notation for Connect Four positions: "4453" is red in the center, yellow on top of it, red
+/// beside it and yellow two columns to the left.
+///
+/// Every sequence is legal: nobody plays into a full column or after the game is won. Like
+/// `BitBoard`, the notation only covers the standard 7 by 6 board, not other `Board` sizes.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct MoveSequence {
+    /// zero-based, like the rest of the crate.
+    columns: Vec<usize>,
+    board: BitBoard,
+}
+
+impl MoveSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A sequence from zero-based column indices.
+    pub fn from_columns(columns: &[usize]) -> Result<Self> {
+        let mut out = Self::new();
+        for (idx, &col) in columns.iter().enumerate() {
+            out.push(col)
+                .map_err(|error| Error::IllegalMove(idx, Box::new(error)))?;
+        }
+        Ok(out)
+    }
+
+    /// The zero-based columns played so far.
+    pub fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Plays the zero-based column `col` for whoever is to move.
+    pub fn push(&mut self, col: usize) -> Result<()> {
+        if self.board.reward().is_some() {
+            return Err(Error::GameOver);
+        }
+        self.board = self
+            .board
+            .try_move(self.board.current_player().unwrap(), col)?;
+        self.columns.push(col);
+        Ok(())
+    }
+
+    pub fn bitboard(&self) -> BitBoard {
+        self.board
+    }
+
+    pub fn board(&self) -> Board<Option<Checker>> {
+        Board::from(self.board)
+    }
+
+    pub fn game(&self) -> Game {
+        self.columns
+            .iter()
+            .try_fold(Game::default(), |game, &col| game.update(col))
+            .expect("a move sequence to only hold legal moves.")
+    }
+}
+
+impl FromStr for MoveSequence {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut out = Self::new();
+        for (idx, c) in s.chars().enumerate() {
+            let col = match c.to_digit(10) {
+                Some(digit @ 1..=7) => digit as usize - 1,
+                _ => return Err(Error::NotAColumn(c, idx)),
+            };
+            out.push(col)
+                .map_err(|error| Error::IllegalNotation(c, idx, Box::new(error)))?;
+        }
+        Ok(out)
+    }
+}
+
+impl Display for MoveSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.columns
+            .iter()
+            .try_for_each(|col| write!(f, "{}", col + 1))
+    }
+}
+
+impl From<&MoveSequence> for BitBoard {
+    fn from(moves: &MoveSequence) -> Self {
+        moves.bitboard()
+    }
+}
+
+impl From<&MoveSequence> for Board<Option<Checker>> {
+    fn from(moves: &MoveSequence) -> Self {
+        moves.board()
+    }
+}
+
+impl From<&MoveSequence> for Game {
+    fn from(moves: &MoveSequence) -> Self {
+        moves.game()
+    }
+}
+
+/// Finds a sequence of moves that reaches `board`. Boards usually have many; this returns one
+/// of them, or `Error::Unreachable` if checkers float, the counts are off, or the game would
+/// have ended before the board filled up this far.
+impl TryFrom<&Board<Option<Checker>>> for MoveSequence {
+    type Error = Error;
+
+    fn try_from(board: &Board<Option<Checker>>) -> Result<Self> {
+        unwind(BitBoard::from(board), None)
+    }
+}
+
+/// Like the conversion from `Board`, but the sequence also ends with the game's last move.
+impl TryFrom<&Game> for MoveSequence {
+    type Error = Error;
+
+    fn try_from(game: &Game) -> Result<Self> {
+        let last = game.previous_move().map(|&(_, col)| col);
+        unwind(BitBoard::from(game.board()), last)
+    }
+}
+
+/// a sequence of moves reaching `board`, whose final move was in column `last` if given.
+fn unwind(board: BitBoard, last: Option<usize>) -> Result<MoveSequence> {
+    let red = board.mask(Checker::Red).count_ones();
+    let yellow = board.mask(Checker::Yellow).count_ones();
+    if !board.is_settled() || !(red == yellow || red == yellow + 1) {
+        return Err(Error::Unreachable);
+    }
+
+    let mut columns = Vec::new();
+    if !take_back(board, last, &mut HashSet::new(), &mut columns) {
+        return Err(Error::Unreachable);
+    }
+    columns.reverse();
+    MoveSequence::from_columns(&columns)
+}
+
+/// unplays checkers one at a time, pushing their columns onto `columns` in reverse, until the
+/// board is empty. `only` restricts the first checker taken back to one column, and
+/// `dead_ends` remembers boards that can't be unwound, so each is tried once.
+fn take_back(
+    board: BitBoard,
+    only: Option<usize>,
+    dead_ends: &mut HashSet<BitBoard>,
+    columns: &mut Vec<usize>,
+) -> bool {
+    if board.occupied() == 0 {
+        return true;
+    }
+    if only.is_none() && dead_ends.contains(&board) {
+        return false;
+    }
+
+    let last = board.current_player().unwrap().opponent();
+    for col in only.map_or(0..7, |col| col..col + 1) {
+        let Some((player, before)) = board.take_back(col) else {
+            continue;
+        };
+        // nobody may have won before the last checker went in
+        if player != last || before.reward().is_some() {
+            continue;
+        }
+        columns.push(col);
+        if take_back(before, None, dead_ends, columns) {
+            return true;
+        }
+        columns.pop();
+    }
+
+    if only.is_none() {
+        dead_ends.insert(board);
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<MoveSequence> {
+        s.parse()
+    }
+
+    #[test]
+    fn round_trips_through_strings() {
+        for s in ["", "4", "4453", "2252576253462244111563365343671351441"] {
+            let moves = parse(s).unwrap();
+            assert_eq!(moves.to_string(), s);
+            assert_eq!(moves.len(), s.len());
+        }
+        assert_eq!(parse("4453").unwrap().columns(), &[3, 3, 4, 2]);
+    }
+
+    #[test]
+    fn reports_illegal_moves() {
+        assert!(matches!(parse("44a"), Err(Error::NotAColumn('a', 2))));
+        assert!(matches!(parse("448"), Err(Error::NotAColumn('8', 2))));
+        assert!(matches!(parse("10"), Err(Error::NotAColumn('0', 1))));
+        assert!(matches!(
+            parse("1111111"),
+            Err(Error::IllegalNotation('1', 6, error)) if matches!(*error, Error::ColumnFull(0))
+        ));
+        assert!(matches!(
+            parse("12121214"),
+            Err(Error::IllegalNotation('4', 7, error)) if matches!(*error, Error::GameOver)
+        ));
+
+        // messages count from one, like the notation
+        let message = |s: &str| parse(s).unwrap_err().to_string();
+        assert_eq!(
+            message("448"),
+            "'8' at position 3 is not a column from 1 to 7 of the standard board"
+        );
+        assert_eq!(
+            message("10"),
+            "'0' at position 2 is not a column from 1 to 7 of the standard board"
+        );
+        assert_eq!(message("1111111"), "'1' at position 7 is a full column");
+        assert_eq!(
+            message("12121214"),
+            "'4' at position 8 is illegal: the game is already over"
+        );
+    }
+
+    #[test]
+    fn converts_to_boards_and_games() {
+        let moves = parse("4453").unwrap();
+        let board = Board::<Option<Checker>>::from(&moves);
+        assert_eq!(
+            Some(board.clone()),
+            Board::from_moves(&[
+                (Checker::Red, 3),
+                (Checker::Yellow, 3),
+                (Checker::Red, 4),
+                (Checker::Yellow, 2),
+            ])
+        );
+
+        let game = Game::from(&moves);
+        assert_eq!(game.board(), &board);
+        assert_eq!(game.previous_move(), Some(&(Checker::Yellow, 2)));
+        assert_eq!(BitBoard::from(&moves), BitBoard::from(&board));
+    }
+
+    #[test]
+    fn recovers_sequences_from_boards() {
+        for s in [
+            "",
+            "4453",
+            "1212121",
+            "2252576253462244111563365343671351441",
+        ] {
+            let moves = parse(s).unwrap();
+            let recovered = MoveSequence::try_from(&moves.board()).unwrap();
+            assert_eq!(recovered.board(), moves.board());
+
+            let recovered = MoveSequence::try_from(&moves.game()).unwrap();
+            assert_eq!(recovered.game(), moves.game());
+        }
+
+        // red has two checkers more than yellow
+        let board = Board::from_moves(&[(Checker::Red, 0), (Checker::Red, 1)]).unwrap();
+        assert!(matches!(
+            MoveSequence::try_from(&board),
+            Err(Error::Unreachable)
+        ));
+
+        // both players have four in a row
+        let board = Board::from_moves(&[0, 1, 0, 1, 0, 1, 0, 1].map(|col| {
+            (
+                if col == 0 {
+                    Checker::Red
+                } else {
+                    Checker::Yellow
+                },
+                col,
+            )
+        }))
+        .unwrap();
+        assert!(matches!(
+            MoveSequence::try_from(&board),
+            Err(Error::Unreachable)
+        ));
+    }
+}
@@ -0,0 +1,333 @@
+use crate::{
+    error::{Error, Result},
+    Board, Checker,
+};
+use common::collections::{GameResult, GameState};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
+
+/// How many times a position may come up before the game is drawn.
+const REPETITIONS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PopOutAction {
+    /// drop a checker into the column, as in the standard game.
+    Drop(usize),
+    /// take your own checker out of the bottom of the column.
+    Pop(usize),
+}
+
+/// Connect Four with PopOut rules: instead of dropping a checker, a player may pop one of their
+/// own checkers out of the bottom row, and the rest of that column falls one row.
+///
+/// A pop can complete lines for both players at once, in which case the player who popped wins.
+/// A full board is not a draw as long as the player to move has something to pop; the game is
+/// drawn when they don't, or when the same position comes up for the third time.
+///
+/// Games compare and hash by position: the board, the player to move and how many times the
+/// position has come up, which decides whether it is a draw. The last move and the rest of the
+/// history are left out, so transpositions compare equal.
+#[derive(Debug, Clone)]
+pub struct PopOut<const W: usize = 7, const H: usize = 6, const K: usize = 4> {
+    board: Board<Option<Checker>, W, H, K>,
+    current_player: Checker,
+    previous_move: Option<(Checker, PopOutAction)>,
+    /// a hash of every position so far, the current one included, for spotting repetitions.
+    history: Vec<u64>,
+    /// how many times the current position has come up, this time included.
+    repetitions: usize,
+}
+
+impl<const W: usize, const H: usize, const K: usize> PopOut<W, H, K> {
+    pub fn new() -> Self {
+        let mut out = Self {
+            board: Board::default(),
+            current_player: Checker::Red,
+            previous_move: None,
+            history: Vec::new(),
+            repetitions: 0,
+        };
+        out.record_position();
+        out
+    }
+
+    pub fn board(&self) -> &Board<Option<Checker>, W, H, K> {
+        &self.board
+    }
+
+    pub fn previous_move(&self) -> Option<&(Checker, PopOutAction)> {
+        self.previous_move.as_ref()
+    }
+
+    /// The winner, if anyone has `K` in a row.
+    pub fn winner(&self) -> Option<Checker> {
+        let red = self.board.has_line(Checker::Red);
+        let yellow = self.board.has_line(Checker::Yellow);
+        match (red, yellow) {
+            // only a pop makes lines for both, and then whoever popped, the player who just
+            // moved, wins
+            (true, true) => Some(self.current_player.opponent()),
+            (true, false) => Some(Checker::Red),
+            (false, true) => Some(Checker::Yellow),
+            (false, false) => None,
+        }
+    }
+
+    /// Whether the current position has come up often enough to draw the game.
+    pub fn is_repetition(&self) -> bool {
+        self.repetitions >= REPETITIONS
+    }
+
+    /// Every legal action for the player to move, drops before pops.
+    pub fn legal_actions(&self) -> Vec<PopOutAction> {
+        if self.winner().is_some() || self.is_repetition() {
+            return Vec::new();
+        }
+        let drops = (0..W)
+            .filter(|&col| self.board.height(col) < H)
+            .map(PopOutAction::Drop);
+        let pops = (0..W)
+            .filter(|&col| self.can_pop(col))
+            .map(PopOutAction::Pop);
+        drops.chain(pops).collect()
+    }
+
+    /// Plays `action` for the player to move.
+    pub fn update(self, action: PopOutAction) -> Result<Self> {
+        if self.winner().is_some() || self.is_repetition() {
+            return Err(Error::GameOver);
+        }
+
+        let board = match action {
+            PopOutAction::Drop(col) => self.board.try_move(self.current_player, col)?,
+            PopOutAction::Pop(col) if self.can_pop(col) => self.board.try_pop(col)?.1,
+            PopOutAction::Pop(col) if col >= W => return Err(Error::InvalidColumn(col)),
+            PopOutAction::Pop(col) => return Err(Error::CannotPop(col)),
+        };
+
+        let mut out = Self {
+            board,
+            current_player: self.current_player.opponent(),
+            previous_move: Some((self.current_player, action)),
+            history: self.history,
+            repetitions: 0,
+        };
+        out.record_position();
+        Ok(out)
+    }
+
+    fn can_pop(&self, col: usize) -> bool {
+        self.board.get(H - 1, col) == Some(&Some(self.current_player))
+    }
+
+    /// adds the current position to the history and counts how often it has come up.
+    fn record_position(&mut self) {
+        let current = self.position_hash();
+        self.history.push(current);
+        self.repetitions = self.history.iter().filter(|&&hash| hash == current).count();
+    }
+
+    fn position_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.board.hash(&mut hasher);
+        self.current_player.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> PartialEq for PopOut<W, H, K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board
+            && self.current_player == other.current_player
+            && self.repetitions == other.repetitions
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> Eq for PopOut<W, H, K> {}
+
+impl<const W: usize, const H: usize, const K: usize> Hash for PopOut<W, H, K> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.board.hash(state);
+        self.current_player.hash(state);
+        self.repetitions.hash(state);
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> Default for PopOut<W, H, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> Display for PopOut<W, H, K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.board)
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> GameState for PopOut<W, H, K> {
+    type Reward = f64;
+    type Action = PopOutAction;
+    type Player = Checker;
+    type ActionIter = std::vec::IntoIter<(Self::Player, Self::Action)>;
+    type Error = Error;
+
+    fn current_player(&self) -> Self::Player {
+        self.current_player
+    }
+
+    /// One point to the winner, or half a point each for a draw.
+    fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
+        match self.winner() {
+            Some(winner) => HashMap::from([(winner, 1.0)]),
+            None if self.is_complete() => {
+                HashMap::from([(Checker::Red, 0.5), (Checker::Yellow, 0.5)])
+            }
+            None => HashMap::default(),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.legal_actions().is_empty()
+    }
+
+    fn result(&self) -> Option<GameResult<Self::Player>> {
+        match self.winner() {
+            Some(winner) => Some(GameResult::Win(winner)),
+            None => self.is_complete().then_some(GameResult::Draw),
+        }
+    }
+
+    fn action_iter(&self) -> Self::ActionIter {
+        self.legal_actions()
+            .into_iter()
+            .map(|action| (self.current_player, action))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn step(self, player: &Self::Player, action: &Self::Action) -> Result<Self> {
+        if *player != self.current_player {
+            return Err(Error::WrongPlayer(*player));
+        }
+        self.update(*action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::collections::{MonteCarloTree, MoveSelection, SearchBudget, Ucb1};
+    use PopOutAction::{Drop, Pop};
+
+    fn play(actions: &[PopOutAction]) -> Result<PopOut> {
+        actions
+            .iter()
+            .try_fold(PopOut::new(), |game, &action| game.update(action))
+    }
+
+    #[test]
+    fn pops_own_checkers_only() {
+        let game = play(&[Drop(3), Drop(4), Drop(3)]).unwrap();
+        assert_eq!(game.current_player(), Checker::Yellow);
+        assert!(game.legal_actions().contains(&Pop(4)));
+        assert!(!game.legal_actions().contains(&Pop(3)));
+        assert!(matches!(
+            game.clone().update(Pop(3)),
+            Err(Error::CannotPop(3))
+        ));
+        assert!(matches!(
+            game.clone().update(Pop(0)),
+            Err(Error::CannotPop(0))
+        ));
+
+        // popping red's bottom checker lets the one above fall
+        let game = game.update(Drop(6)).unwrap().update(Pop(3)).unwrap();
+        assert_eq!(game.board().height(3), 1);
+        assert_eq!(game.board().get(5, 3), Some(&Some(Checker::Red)));
+    }
+
+    #[test]
+    fn popper_wins_double_lines() {
+        // column 0 holds red, yellow, red from the bottom, next to three yellows on the bottom
+        // row and three reds above them
+        let game = play(&[
+            Drop(0),
+            Drop(0),
+            Drop(0),
+            Drop(1),
+            Drop(1),
+            Drop(2),
+            Drop(2),
+            Drop(3),
+            Drop(3),
+            Drop(6),
+        ])
+        .unwrap();
+        assert_eq!(game.winner(), None, "\n{}", game);
+
+        // popping red's checker lines up both rows at once
+        let game = game.update(Pop(0)).unwrap();
+        assert!(game.board().has_line(Checker::Yellow), "\n{}", game);
+        assert_eq!(game.winner(), Some(Checker::Red));
+        assert!(game.is_complete());
+    }
+
+    #[test]
+    fn transpositions_compare_equal() {
+        let game = play(&[Drop(0), Drop(1), Drop(2), Drop(3)]).unwrap();
+        let other = play(&[Drop(2), Drop(3), Drop(0), Drop(1)]).unwrap();
+        assert_eq!(game, other);
+        let hash = |game: &PopOut| {
+            let mut hasher = DefaultHasher::new();
+            game.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&game), hash(&other));
+        assert_ne!(game, play(&[Drop(0), Drop(1), Drop(3), Drop(2)]).unwrap());
+    }
+
+    #[test]
+    fn threefold_repetition_draws() {
+        // after an opening drop each, red and yellow drop into columns 6 and 5 and pop those
+        // checkers straight back out, twice over
+        let mut game = play(&[Drop(0), Drop(1)]).unwrap();
+        for _ in 0..2 {
+            assert!(!game.is_complete());
+            game = game
+                .update(Drop(6))
+                .and_then(|game| game.update(Drop(5)))
+                .and_then(|game| game.update(Pop(6)))
+                .and_then(|game| game.update(Pop(5)))
+                .unwrap();
+        }
+        assert!(game.is_repetition());
+        assert!(game.is_complete());
+        assert_eq!(
+            game.reward(),
+            HashMap::from([(Checker::Red, 0.5), (Checker::Yellow, 0.5)])
+        );
+
+        // the same board with the same player to move, seen for the first time, plays on
+        let first = play(&[Drop(0), Drop(1)]).unwrap();
+        assert_eq!(
+            (first.board(), first.current_player()),
+            (game.board(), game.current_player())
+        );
+        assert!(!first.is_complete());
+        assert_ne!(first, game);
+    }
+
+    #[test]
+    fn searches_with_mcts() {
+        // red can win by completing the bottom row
+        let game = play(&[Drop(0), Drop(0), Drop(1), Drop(1), Drop(2), Drop(2)]).unwrap();
+        let mut tree = MonteCarloTree::seed_from_state(0, game);
+        tree.search(&Ucb1::default(), &SearchBudget::new().iterations(2000));
+        let best = tree.best_action(MoveSelection::RobustChild).unwrap();
+        assert_eq!(best.action, Drop(3));
+    }
+}
@@ -0,0 +1,448 @@
+use crate::{
+    error::{Error, Result},
+    Board, Checker,
+};
+use common::collections::{GameResult, GameState};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
+
+/// How many checkers a player has to collect to win.
+const TARGET: usize = 10;
+
+/// How many times a position may come up before the game is drawn.
+const REPETITIONS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PopTenAction {
+    /// drop a checker into the column: while setting up, or to return a popped checker.
+    Drop(usize),
+    /// take your own checker out of the bottom of the column.
+    Pop(usize),
+    /// skip the turn, when there is nothing of yours to pop.
+    Pass,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PopTenPhase {
+    /// players take turns filling the board, finishing each row before starting the next.
+    Setup,
+    /// the player to move pops one of their checkers from the bottom row.
+    Pop,
+    /// the player to move popped a checker from this column that wasn't part of a line, and
+    /// has to drop it back in somewhere else.
+    Return(usize),
+}
+
+/// Connect Four's Pop Ten variant. The players first fill the board together, bottom row first,
+/// and then take turns popping their own checkers out of the bottom row. A checker that was part
+/// of `K` in a row is kept, and the player pops again; any other checker goes back on top of a
+/// different column, if one has room, and the turn passes. The first player to keep ten
+/// checkers wins, and the game is drawn when a position comes up for the third time.
+///
+/// Like `PopOut`, games compare and hash by position and how many times it has come up, leaving
+/// out the last move and the rest of the history.
+#[derive(Debug, Clone)]
+pub struct PopTen<const W: usize = 7, const H: usize = 6, const K: usize = 4> {
+    board: Board<Option<Checker>, W, H, K>,
+    current_player: Checker,
+    phase: PopTenPhase,
+    /// checkers kept by red and by yellow.
+    collected: [usize; 2],
+    previous_move: Option<(Checker, PopTenAction)>,
+    /// a hash of every position since someone last kept a checker, the current one included.
+    /// keeping a checker can't be undone, so earlier positions never come up again.
+    history: Vec<u64>,
+    /// how many times the current position has come up, this time included.
+    repetitions: usize,
+}
+
+impl<const W: usize, const H: usize, const K: usize> PopTen<W, H, K> {
+    pub fn new() -> Self {
+        let mut out = Self {
+            board: Board::default(),
+            current_player: Checker::Red,
+            phase: PopTenPhase::Setup,
+            collected: [0; 2],
+            previous_move: None,
+            history: Vec::new(),
+            repetitions: 0,
+        };
+        out.record_position();
+        out
+    }
+
+    pub fn board(&self) -> &Board<Option<Checker>, W, H, K> {
+        &self.board
+    }
+
+    pub fn phase(&self) -> PopTenPhase {
+        self.phase
+    }
+
+    pub fn previous_move(&self) -> Option<&(Checker, PopTenAction)> {
+        self.previous_move.as_ref()
+    }
+
+    /// The number of checkers `player` has kept so far.
+    pub fn collected(&self, player: Checker) -> usize {
+        self.collected[player as usize]
+    }
+
+    /// The first player to keep ten checkers.
+    pub fn winner(&self) -> Option<Checker> {
+        [Checker::Red, Checker::Yellow]
+            .into_iter()
+            .find(|&player| self.collected(player) >= TARGET)
+    }
+
+    /// Whether the current position has come up often enough to draw the game.
+    pub fn is_repetition(&self) -> bool {
+        self.repetitions >= REPETITIONS
+    }
+
+    /// Every legal action for the player to move.
+    pub fn legal_actions(&self) -> Vec<PopTenAction> {
+        if self.winner().is_some() || self.is_repetition() {
+            return Vec::new();
+        }
+        match self.phase {
+            PopTenPhase::Setup => {
+                let lowest = (0..W).map(|col| self.board.height(col)).min();
+                (0..W)
+                    .filter(|&col| Some(self.board.height(col)) == lowest)
+                    .map(PopTenAction::Drop)
+                    .collect()
+            }
+            PopTenPhase::Pop => {
+                let pops = (0..W)
+                    .filter(|&col| self.can_pop(col))
+                    .map(PopTenAction::Pop)
+                    .collect::<Vec<_>>();
+                if pops.is_empty() {
+                    vec![PopTenAction::Pass]
+                } else {
+                    pops
+                }
+            }
+            PopTenPhase::Return(from) => {
+                let open = (0..W)
+                    .filter(|&col| col != from && self.board.height(col) < H)
+                    .map(PopTenAction::Drop)
+                    .collect::<Vec<_>>();
+                if open.is_empty() {
+                    vec![PopTenAction::Drop(from)]
+                } else {
+                    open
+                }
+            }
+        }
+    }
+
+    /// Plays `action` for the player to move.
+    pub fn update(self, action: PopTenAction) -> Result<Self> {
+        if self.winner().is_some() || self.is_repetition() {
+            return Err(Error::GameOver);
+        }
+        let legal = self.legal_actions();
+        if !legal.contains(&action) {
+            return Err(match action {
+                PopTenAction::Drop(col) | PopTenAction::Pop(col) if col >= W => {
+                    Error::InvalidColumn(col)
+                }
+                PopTenAction::Drop(col) if self.board.height(col) == H => Error::ColumnFull(col),
+                PopTenAction::Drop(col) => Error::CannotDrop(col),
+                PopTenAction::Pop(col) => Error::CannotPop(col),
+                PopTenAction::Pass => Error::CannotPass,
+            });
+        }
+
+        let player = self.current_player;
+        let mut out = Self {
+            previous_move: Some((player, action)),
+            ..self
+        };
+        match action {
+            PopTenAction::Drop(col) => {
+                out.board = out.board.try_move(player, col)?;
+                out.current_player = player.opponent();
+                if out.phase != PopTenPhase::Setup || out.board.is_full() {
+                    out.phase = PopTenPhase::Pop;
+                }
+            }
+            PopTenAction::Pop(col) => {
+                let kept = out.board.in_line(H - 1, col);
+                out.board = out.board.try_pop(col)?.1;
+                if kept {
+                    out.collected[player as usize] += 1;
+                    out.history.clear();
+                } else {
+                    out.phase = PopTenPhase::Return(col);
+                }
+            }
+            PopTenAction::Pass => out.current_player = player.opponent(),
+        }
+        out.record_position();
+        Ok(out)
+    }
+
+    fn can_pop(&self, col: usize) -> bool {
+        self.board.get(H - 1, col) == Some(&Some(self.current_player))
+    }
+
+    /// adds the current position to the history and counts how often it has come up.
+    fn record_position(&mut self) {
+        let current = self.position_hash();
+        self.history.push(current);
+        self.repetitions = self.history.iter().filter(|&&hash| hash == current).count();
+    }
+
+    fn position_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.board.hash(&mut hasher);
+        self.current_player.hash(&mut hasher);
+        self.phase.hash(&mut hasher);
+        self.collected.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> PartialEq for PopTen<W, H, K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board
+            && self.current_player == other.current_player
+            && self.phase == other.phase
+            && self.collected == other.collected
+            && self.repetitions == other.repetitions
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> Eq for PopTen<W, H, K> {}
+
+impl<const W: usize, const H: usize, const K: usize> Hash for PopTen<W, H, K> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.board.hash(state);
+        self.current_player.hash(state);
+        self.phase.hash(state);
+        self.collected.hash(state);
+        self.repetitions.hash(state);
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> Default for PopTen<W, H, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> Display for PopTen<W, H, K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.board)
+    }
+}
+
+impl<const W: usize, const H: usize, const K: usize> GameState for PopTen<W, H, K> {
+    type Reward = f64;
+    type Action = PopTenAction;
+    type Player = Checker;
+    type ActionIter = std::vec::IntoIter<(Self::Player, Self::Action)>;
+    type Error = Error;
+
+    fn current_player(&self) -> Self::Player {
+        self.current_player
+    }
+
+    /// One point to the winner, or half a point each for a draw.
+    fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
+        match self.winner() {
+            Some(winner) => HashMap::from([(winner, 1.0)]),
+            None if self.is_repetition() => {
+                HashMap::from([(Checker::Red, 0.5), (Checker::Yellow, 0.5)])
+            }
+            None => HashMap::default(),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.winner().is_some() || self.is_repetition()
+    }
+
+    fn result(&self) -> Option<GameResult<Self::Player>> {
+        match self.winner() {
+            Some(winner) => Some(GameResult::Win(winner)),
+            None => self.is_repetition().then_some(GameResult::Draw),
+        }
+    }
+
+    fn action_iter(&self) -> Self::ActionIter {
+        self.legal_actions()
+            .into_iter()
+            .map(|action| (self.current_player, action))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn step(self, player: &Self::Player, action: &Self::Action) -> Result<Self> {
+        if *player != self.current_player {
+            return Err(Error::WrongPlayer(*player));
+        }
+        self.update(*action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::collections::{MonteCarloTree, MoveSelection, SearchBudget, Ucb1};
+    use nanorand::{Rng, WyRand};
+    use PopTenAction::{Drop, Pass, Pop};
+
+    /// fills the board row by row, each row left to right, so the colors alternate like a
+    /// checkerboard and every checker is part of a diagonal line.
+    fn checkerboard() -> PopTen {
+        (0..42).fold(PopTen::new(), |game, idx| {
+            game.update(Drop(idx % 7)).unwrap()
+        })
+    }
+
+    #[test]
+    fn setup_fills_rows_in_order() {
+        let game = PopTen::<7, 6, 4>::new().update(Drop(3)).unwrap();
+        assert_eq!(
+            game.legal_actions(),
+            vec![Drop(0), Drop(1), Drop(2), Drop(4), Drop(5), Drop(6)]
+        );
+        assert!(matches!(game.update(Drop(3)), Err(Error::CannotDrop(3))));
+
+        let game = checkerboard();
+        assert!(game.board().is_full());
+        assert_eq!(game.phase(), PopTenPhase::Pop);
+        assert_eq!(game.current_player(), Checker::Red);
+    }
+
+    #[test]
+    fn transpositions_compare_equal() {
+        let play = |moves: [usize; 4]| {
+            moves
+                .into_iter()
+                .try_fold(PopTen::<7, 6, 4>::new(), |game, col| game.update(Drop(col)))
+                .unwrap()
+        };
+        assert_eq!(play([0, 1, 2, 3]), play([2, 3, 0, 1]));
+        assert_ne!(play([0, 1, 2, 3]), play([0, 1, 3, 2]));
+    }
+
+    #[test]
+    fn keeps_checkers_from_lines() {
+        let game = checkerboard();
+        assert!(matches!(
+            game.clone().update(Pop(1)),
+            Err(Error::CannotPop(1))
+        ));
+        assert!(matches!(game.clone().update(Pass), Err(Error::CannotPass)));
+
+        // red's bottom left checker is on a diagonal, so red keeps it and goes again
+        let game = game.update(Pop(0)).unwrap();
+        assert_eq!(game.collected(Checker::Red), 1);
+        assert_eq!(game.phase(), PopTenPhase::Pop);
+        assert_eq!(game.current_player(), Checker::Red);
+        assert_eq!(game.board().height(0), 5);
+    }
+
+    #[test]
+    fn returns_other_checkers() {
+        let mut rng = WyRand::new_seed(0);
+        let mut returned = 0;
+        while returned < 10 {
+            let mut game = PopTen::<7, 6, 4>::new();
+            while game.phase() == PopTenPhase::Setup {
+                let actions = game.legal_actions();
+                game = game
+                    .update(actions[rng.generate_range(0..actions.len())])
+                    .unwrap();
+            }
+            let Some(&Pop(col)) = game
+                .legal_actions()
+                .iter()
+                .find(|action| matches!(action, Pop(col) if !game.board().in_line(5, *col)))
+            else {
+                continue;
+            };
+
+            // the only open column is the one the checker came from
+            let player = game.current_player();
+            let game = game.update(Pop(col)).unwrap();
+            assert_eq!(game.phase(), PopTenPhase::Return(col));
+            assert_eq!(game.legal_actions(), vec![Drop(col)]);
+            assert_eq!(game.current_player(), player);
+
+            let game = game.update(Drop(col)).unwrap();
+            assert_eq!(game.phase(), PopTenPhase::Pop);
+            assert_eq!(game.current_player(), player.opponent());
+            assert_eq!(game.collected(player), 0);
+            returned += 1;
+        }
+    }
+
+    #[test]
+    fn random_games_finish() {
+        let mut rng = WyRand::new_seed(1);
+        for _ in 0..20 {
+            let mut game = PopTen::<7, 6, 4>::new();
+            while !game.is_complete() {
+                let actions = game.legal_actions();
+                if actions.contains(&Pass) {
+                    assert_eq!(actions, vec![Pass]);
+                }
+                if let PopTenPhase::Return(col) = game.phase() {
+                    assert!(
+                        actions.iter().all(|&action| action != Drop(col)) || actions.len() == 1
+                    );
+                }
+                game = game
+                    .update(actions[rng.generate_range(0..actions.len())])
+                    .unwrap();
+            }
+            let reward = game.reward();
+            assert!(game.winner().is_some() || reward.len() == 2, "{:?}", reward);
+        }
+    }
+
+    #[test]
+    fn repetitions_tell_positions_apart() {
+        // play random games until one is drawn by repetition, keeping every position
+        let mut rng = WyRand::new_seed(2);
+        let seen = loop {
+            let mut seen = vec![PopTen::<7, 6, 4>::new()];
+            while !seen.last().unwrap().is_complete() {
+                let game = seen.last().unwrap().clone();
+                let actions = game.legal_actions();
+                seen.push(
+                    game.update(actions[rng.generate_range(0..actions.len())])
+                        .unwrap(),
+                );
+            }
+            if seen.last().unwrap().is_repetition() {
+                break seen;
+            }
+        };
+
+        // the same position came up earlier, when it didn't end the game
+        let last = seen.last().unwrap();
+        let earlier = seen
+            .iter()
+            .find(|game| game.position_hash() == last.position_hash())
+            .unwrap();
+        assert!(!earlier.is_complete());
+        assert_ne!(earlier, last);
+    }
+
+    #[test]
+    fn searches_with_mcts() {
+        let mut tree = MonteCarloTree::<PopTen>::seed_from_u64(0);
+        tree.search(&Ucb1::default(), &SearchBudget::new().iterations(200));
+        assert!(tree.best_action(MoveSelection::RobustChild).is_some());
+    }
+}
@@ -0,0 +1,397 @@
+use crate::{BitBoard, Checker};
+
+/// columns in the order they are searched, center first, since central checkers take part in
+/// the most fours.
+const ORDER: [usize; 7] = [3, 2, 4, 1, 5, 0, 6];
+
+/// the bottom cell of every column, in `BitBoard` layout.
+const BOTTOM: u64 = 0b0000001_0000001_0000001_0000001_0000001_0000001_0000001;
+
+/// every playable cell, leaving out the empty row above each column.
+const PLAYABLE: u64 = BOTTOM * 0b111111;
+
+/// transposition table size used by `Solver::new`, about a megabyte. a prime, so keys spread
+/// evenly. deep searches from early positions go faster with a larger table from
+/// `Solver::with_capacity`.
+const DEFAULT_CAPACITY: usize = 65_521;
+
+/// An exact Connect Four solver.
+///
+/// Scores are from the point of view of the player to move. A positive score means that player
+/// wins, a negative score means they lose, and zero is a draw. The sooner the win the larger the
+/// score: winning with your last of 21 checkers scores 1, winning with your 20th scores 2, and so
+/// on.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    table: Vec<Option<Entry>>,
+    nodes: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    key: u64,
+    bound: Bound,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Bound {
+    Lower(i8),
+    Upper(i8),
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// A solver whose transposition table holds `capacity` positions.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            table: vec![None; capacity.max(1)],
+            nodes: 0,
+        }
+    }
+
+    /// Positions searched since the solver was created or last reset.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Clears the transposition table and the node count.
+    pub fn reset(&mut self) {
+        self.table.iter_mut().for_each(|entry| *entry = None);
+        self.nodes = 0;
+    }
+
+    /// The exact score of `board` for the player to move.
+    pub fn solve(&mut self, board: impl Into<BitBoard>) -> i32 {
+        let board = board.into();
+        if let Some((winner, _)) = board.reward() {
+            // the previous player has already won
+            return -(22 - board.mask(winner).count_ones() as i32);
+        } else if board.is_full() {
+            return 0;
+        }
+
+        let position = Position::from(board);
+        if position.can_win_next() {
+            return (43 - position.moves) / 2;
+        }
+
+        // iterative deepening: look for a forced result one move further ahead each time, a
+        // loss on the opponent's moves and a win on ours. each probe is a null-window search
+        // that only asks whether the score reaches that result, so everything slower is cut off
+        // and the shallow probes stay cheap. the first result found is the exact score, since
+        // every quicker one was already ruled out, and nothing by the end of the board is a draw
+        for depth in 2..=42 - position.moves {
+            let score = (44 - position.moves - depth) / 2;
+            if depth % 2 == 0 {
+                let loss = -score;
+                if self.negamax(position, loss, loss + 1) <= loss {
+                    return loss;
+                }
+            } else if self.negamax(position, score - 1, score) >= score {
+                return score;
+            }
+        }
+        0
+    }
+
+    /// The exact score of every column for the player to move, or `None` for columns that are
+    /// full. All `None` when the game is already over.
+    pub fn analyze(&mut self, board: impl Into<BitBoard>) -> [Option<i32>; 7] {
+        let board = board.into();
+        let mut scores = [None; 7];
+        if board.reward().is_some() {
+            return scores;
+        }
+
+        let player = board.current_player().unwrap();
+        for col in ORDER {
+            if let Some(child) = board.play_move(player, col) {
+                scores[col] = Some(if child.reward().is_some() {
+                    (43 - board.occupied().count_ones() as i32) / 2
+                } else {
+                    -self.solve(child)
+                });
+            }
+        }
+        scores
+    }
+
+    /// The best column for the player to move and its score, preferring central columns among
+    /// equals. `None` when the game is already over.
+    pub fn best_move(&mut self, board: impl Into<BitBoard>) -> Option<(usize, i32)> {
+        let scores = self.analyze(board);
+        ORDER
+            .into_iter()
+            .filter_map(|col| scores[col].map(|score| (col, score)))
+            .reduce(|best, next| if next.1 > best.1 { next } else { best })
+    }
+
+    /// Negamax with alpha-beta pruning. Assumes nobody has won yet and the player to move
+    /// cannot win immediately.
+    fn negamax(&mut self, position: Position, mut alpha: i32, mut beta: i32) -> i32 {
+        self.nodes += 1;
+
+        let candidates = position.non_losing_moves();
+        if candidates == 0 {
+            // every move hands the opponent a win
+            return -(42 - position.moves) / 2;
+        }
+        if position.moves >= 40 {
+            // the last two checkers cannot make a four for either player
+            return 0;
+        }
+
+        // the opponent can't win on their next move, and neither can we on this one
+        let min = -(40 - position.moves) / 2;
+        if alpha < min {
+            alpha = min;
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+        let max = (41 - position.moves) / 2;
+        if beta > max {
+            beta = max;
+            if alpha >= beta {
+                return beta;
+            }
+        }
+
+        let key = position.key();
+        match self.lookup(key) {
+            Some(Bound::Lower(lower)) if alpha < lower as i32 => {
+                alpha = lower as i32;
+                if alpha >= beta {
+                    return alpha;
+                }
+            }
+            Some(Bound::Upper(upper)) if beta > upper as i32 => {
+                beta = upper as i32;
+                if alpha >= beta {
+                    return beta;
+                }
+            }
+            _ => {}
+        }
+
+        // within the center-first order, try the moves that leave us the most ways to win first
+        let mut moves = ORDER
+            .into_iter()
+            .map(|col| candidates & column_mask(col))
+            .filter(|&play| play != 0)
+            .map(|play| (position.threats_after(play), play))
+            .collect::<Vec<_>>();
+        moves.sort_by_key(|&(threats, _)| std::cmp::Reverse(threats));
+
+        for (_, play) in moves {
+            let score = -self.negamax(position.play(play), -beta, -alpha);
+            if score >= beta {
+                self.store(key, Bound::Lower(score as i8));
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+
+        self.store(key, Bound::Upper(alpha as i8));
+        alpha
+    }
+
+    fn lookup(&self, key: u64) -> Option<Bound> {
+        self.table[self.slot(key)]
+            .filter(|entry| entry.key == key)
+            .map(|entry| entry.bound)
+    }
+
+    fn store(&mut self, key: u64, bound: Bound) {
+        let slot = self.slot(key);
+        self.table[slot] = Some(Entry { key, bound });
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        (key % self.table.len() as u64) as usize
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// the view of a `BitBoard` the search works with: the checkers of the player to move and of
+/// both players together.
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    current: u64,
+    occupied: u64,
+    moves: i32,
+}
+
+impl Position {
+    /// plays the move at `cell`, a single bit in the `BitBoard` layout.
+    fn play(self, cell: u64) -> Self {
+        Self {
+            current: self.current ^ self.occupied,
+            occupied: self.occupied | cell,
+            moves: self.moves + 1,
+        }
+    }
+
+    /// unique per position: adding `occupied` sets the bit above each column's top checker.
+    fn key(&self) -> u64 {
+        self.current + self.occupied
+    }
+
+    /// the lowest empty cell of every column that isn't full.
+    fn playable(&self) -> u64 {
+        (self.occupied + BOTTOM) & PLAYABLE
+    }
+
+    /// how many empty cells would complete a four for the player to move after they play at
+    /// `cell`.
+    fn threats_after(&self, cell: u64) -> u32 {
+        winning_cells(self.current | cell, self.occupied | cell).count_ones()
+    }
+
+    fn can_win_next(&self) -> bool {
+        winning_cells(self.current, self.occupied) & self.playable() != 0
+    }
+
+    /// playable cells that don't give the opponent an immediate win, or zero if there are none.
+    fn non_losing_moves(&self) -> u64 {
+        let mut playable = self.playable();
+        let threats = winning_cells(self.current ^ self.occupied, self.occupied);
+        let forced = playable & threats;
+        if forced != 0 {
+            if forced & (forced - 1) != 0 {
+                // two threats can't both be blocked
+                return 0;
+            }
+            playable = forced;
+        }
+        // never play directly below one of the opponent's winning cells
+        playable & !(threats >> 1)
+    }
+}
+
+impl From<BitBoard> for Position {
+    fn from(board: BitBoard) -> Self {
+        let player = board.current_player().unwrap_or(Checker::Red);
+        Self {
+            current: board.mask(player),
+            occupied: board.occupied(),
+            moves: board.occupied().count_ones() as i32,
+        }
+    }
+}
+
+fn column_mask(col: usize) -> u64 {
+    0b111111 << (col * 7)
+}
+
+/// empty cells that would complete a four for the player with checkers `mask`.
+fn winning_cells(mask: u64, occupied: u64) -> u64 {
+    // vertical: only three below can complete a column
+    let mut cells = (mask << 1) & (mask << 2) & (mask << 3);
+
+    // horizontal and both diagonals, with the empty cell at any of the four positions
+    for shift in [7, 6, 8] {
+        let pairs = (mask << shift) & (mask << (2 * shift));
+        cells |= pairs & (mask << (3 * shift));
+        cells |= pairs & (mask >> shift);
+        let pairs = (mask >> shift) & (mask >> (2 * shift));
+        cells |= pairs & (mask << shift);
+        cells |= pairs & (mask >> (3 * shift));
+    }
+
+    cells & (PLAYABLE ^ occupied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Board, MoveSequence};
+    use nanorand::{Rng, WyRand};
+
+    fn board(moves: &str) -> BitBoard {
+        moves.parse::<MoveSequence>().unwrap().bitboard()
+    }
+
+    /// plain negamax over every move, without any of the solver's pruning.
+    fn reference(board: BitBoard) -> i32 {
+        let player = board.current_player().unwrap();
+        let moves = board.occupied().count_ones() as i32;
+        (0..7)
+            .filter_map(|col| board.play_move(player, col))
+            .map(|child| match child.reward() {
+                Some(_) => (43 - moves) / 2,
+                None if child.is_full() => 0,
+                None => -reference(child),
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn scores_immediate_wins_and_losses() {
+        let mut solver = Solver::with_capacity(1 << 16);
+        // red wins with their fourth checker on either end of the bottom row
+        assert_eq!(solver.solve(board("4455")), 22 - 4);
+        // yellow can't block both ends
+        assert_eq!(solver.solve(board("44556")), -(22 - 4));
+        // red has already won
+        assert_eq!(solver.solve(board("4455667")), -(22 - 4));
+    }
+
+    #[test]
+    fn matches_exhaustive_search() {
+        let mut rng = WyRand::new_seed(1);
+        let mut solver = Solver::with_capacity(1 << 16);
+        let mut checked = 0;
+        while checked < 30 {
+            let mut bits = BitBoard::new();
+            while bits.occupied().count_ones() < 31 && bits.reward().is_none() {
+                let moves = bits.available_moves();
+                let (player, col) = moves[rng.generate_range(0..moves.len())];
+                bits = bits.play_move(player, col).unwrap();
+            }
+            if bits.reward().is_some() {
+                continue;
+            }
+            assert_eq!(solver.solve(bits), reference(bits), "\n{}", bits);
+            checked += 1;
+        }
+    }
+
+    #[test]
+    fn best_move_agrees_with_solve() {
+        let mut rng = WyRand::new_seed(2);
+        let mut solver = Solver::with_capacity(1 << 16);
+        for _ in 0..20 {
+            let mut bits = BitBoard::new();
+            while bits.occupied().count_ones() < 30 && bits.reward().is_none() {
+                let moves = bits.available_moves();
+                let (player, col) = moves[rng.generate_range(0..moves.len())];
+                bits = bits.play_move(player, col).unwrap();
+            }
+            if bits.reward().is_some() {
+                assert_eq!(solver.best_move(bits), None);
+                continue;
+            }
+            let (col, score) = solver.best_move(bits).unwrap();
+            assert_eq!(score, solver.solve(bits));
+            assert_eq!(solver.analyze(bits)[col], Some(score));
+        }
+    }
+
+    #[test]
+    fn accepts_boards() {
+        let bits = board("44455554221");
+        let mut solver = Solver::with_capacity(1 << 16);
+        let from_board = solver.solve(Board::from(bits));
+        assert_eq!(from_board, solver.solve(bits));
+    }
+}
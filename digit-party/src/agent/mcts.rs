@@ -1,19 +1,10 @@
-use crate::{
-    error::{Error, Result},
-    Agent, GameState,
-};
+use crate::{Agent, GameState};
+use common::collections::{MonteCarloTree, MoveSelection, SearchBudget, Ucb1};
 use nanorand::{Rng, WyRand};
-use petgraph::{
-    graph::{node_index, NodeIndex},
-    visit::Bfs,
-    Direction::{Incoming, Outgoing},
-    Graph,
-};
-use std::cmp::Ordering;
 
 impl Agent for MonteCarloAgent {
     fn step(&mut self, game: &GameState) -> usize {
-        todo!()
+        MonteCarloAgent::step(self, game)
     }
 }
 
@@ -23,11 +14,23 @@ pub enum MonteCarloTimeBudget {
     Duration(std::time::Duration),
 }
 
+impl From<&MonteCarloTimeBudget> for SearchBudget {
+    fn from(budget: &MonteCarloTimeBudget) -> Self {
+        match budget {
+            MonteCarloTimeBudget::Iterations(n) => SearchBudget::new().iterations(*n),
+            MonteCarloTimeBudget::Duration(d) => SearchBudget::new().duration(*d),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct MonteCarloAgent {
     pub rng: WyRand,
-    pub graph: Graph<MonteCarloNode, MonteCarloEdge>,
     pub budget: MonteCarloTimeBudget,
+    /// exploration constant for UCB1; scores run into the dozens, so this is much larger than
+    /// the usual sqrt(2)
+    pub c: f64,
+    tree: Option<MonteCarloTree<GameState>>,
 }
 
 impl MonteCarloAgent {
@@ -37,56 +40,38 @@ impl MonteCarloAgent {
     }
 
     pub fn seed_from_u64(seed: u64, budget: MonteCarloTimeBudget) -> Self {
-        let mut graph = Graph::default();
-        graph.add_node(MonteCarloNode::default());
-
         Self {
             rng: WyRand::new_seed(seed),
-            graph,
             budget,
+            c: 10.0,
+            tree: None,
         }
     }
 
-    pub fn step(&mut self, state: &GameState) {
-        let mut bfs = Bfs::new(&self.graph, node_index(0));
-        todo!()
-    }
-
-    /// find the next node to explore
-    pub fn select(&self, node_id: NodeIndex, c: f32) -> Result<NodeIndex> {
-        todo!()
-    }
-
-    pub fn expand(&mut self, node_id: NodeIndex) {
-        let state = self
-            .graph
-            .node_weight(node_id)
-            .expect("to find a valid node.")
-            .state
-            .clone();
-
-        for action in state.open_indices() {
-            let state = state.step(action);
-            let node_weight = MonteCarloNode::new(state);
-            let edge_weight = MonteCarloEdge::new(action);
-            let child_id = self.graph.add_node(node_weight);
-            let _ = self.graph.add_edge(node_id, child_id, edge_weight);
-        }
-    }
-
-    pub fn backpropagate(&mut self, node_id: NodeIndex, score: u32) {
-        // starting at a given node, update the score and visits for all ancestors
-        let node = self
-            .graph
-            .node_weight_mut(node_id)
-            .expect("to find a valid node.");
-        node.visits += 1;
-        node.score += score;
-
-        match self.graph.neighbors_directed(node_id, Incoming).next() {
-            Some(parent_id) => self.backpropagate(parent_id, score),
-            None => (),
+    /// Searches from what the player can see of `game` and returns the index to place the
+    /// current digit on. The tree is kept between calls, so the search under the digit that
+    /// actually turned up is reused on the next turn, and dropped when a new game starts.
+    pub fn step(&mut self, game: &GameState) -> usize {
+        if game.open_indices().count() == 25 {
+            // nothing from the last game can be reused
+            self.tree = None;
         }
+        let state = game.observed();
+        let tree = match self.tree.as_mut() {
+            Some(tree) => {
+                tree.reroot(state);
+                tree
+            }
+            None => self
+                .tree
+                .insert(MonteCarloTree::seed_from_state(self.rng.generate(), state)),
+        };
+
+        tree.search(&Ucb1::new(self.c), &SearchBudget::from(&self.budget));
+        tree.best_action(MoveSelection::RobustChild)
+            .map(|best| best.action)
+            .or_else(|| game.open_indices().next())
+            .expect("the game to have an open index.")
     }
 }
 
@@ -97,68 +82,18 @@ impl Default for MonteCarloAgent {
     }
 }
 
-#[derive(Debug)]
-pub struct MonteCarloNode {
-    pub state: GameState,
-    pub visits: u32,
-    pub score: u32,
-}
-
-impl MonteCarloNode {
-    pub fn new(state: GameState) -> Self {
-        Self {
-            state,
-            visits: 0,
-            score: 0,
-        }
-    }
-
-    pub fn uct(&self, c: f32, parent_visits: u32) -> f32 {
-        let n = self.visits as f32;
-
-        if n == 0.0 {
-            f32::INFINITY
-        } else {
-            let w = self.score as f32;
-            let p = parent_visits as f32;
-            w / n + c * (p.ln() / n).sqrt()
-        }
-    }
-
-    /// play randomly until the end of the game, returning the final score
-    pub fn rollout(&self, rng: &mut WyRand) -> u32 {
-        let mut game = self.state.clone();
-        loop {
-            if game.is_complete() {
-                break;
-            }
-
-            let actions = game.open_indices().collect::<Vec<_>>();
-            let idx = rng.generate_range(0..actions.len());
-            game = game.step(actions[idx]);
-        }
-        game.score()
-    }
-}
-
-impl Default for MonteCarloNode {
-    fn default() -> Self {
-        Self {
-            state: GameState::default(),
-            visits: 0,
-            score: 0,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_an_open_index() {
+        let mut agent = MonteCarloAgent::seed_from_u64(0, MonteCarloTimeBudget::Iterations(200));
+        let mut game = GameState::seed_from_u64(0);
+        while !game.is_complete() {
+            let idx = agent.step(&game);
+            assert!(game.open_indices().any(|open| open == idx));
+            game = game.step(idx);
         }
     }
 }
-
-#[derive(Debug)]
-pub struct MonteCarloEdge {
-    pub action: usize,
-    pub visits: u32,
-}
-
-impl MonteCarloEdge {
-    pub fn new(action: usize) -> Self {
-        Self { action, visits: 0 }
-    }
-}
@@ -1,7 +1,7 @@
 mod mcts;
 mod random;
 
-pub use mcts::MonteCarloAgent;
+pub use mcts::{MonteCarloAgent, MonteCarloTimeBudget};
 pub use random::RandomAgent;
 
 use crate::GameState;
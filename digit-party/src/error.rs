@@ -1,16 +1,33 @@
-use petgraph::graph::NodeIndex;
-
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
-    InvalidNode(NodeIndex),
+    InvalidAction(usize),
+    InvalidDigit(usize),
+    /// a line of a printed game, counting from zero, that doesn't match the format.
+    MalformedLine(usize),
+    /// a printed game with this many lines, rather than seven, or eight with the digits shown.
+    WrongLineCount(usize),
+    InvalidCharacter(char),
+    /// a printed game showing this many digits still to place, with this many empty cells.
+    ShownDigits(usize, usize),
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Error::InvalidNode(node_id) => write!(f, "Invalid node: {:?}.", node_id),
+            Error::InvalidAction(idx) => write!(f, "Cannot place a digit at index {}.", idx),
+            Error::InvalidDigit(digit) => write!(f, "Invalid digit: {}.", digit),
+            Error::MalformedLine(line) => write!(f, "Line {} is not part of a board.", line + 1),
+            Error::WrongLineCount(lines) => {
+                write!(f, "Expected a board of 7 or 8 lines, found {}.", lines)
+            }
+            Error::InvalidCharacter(c) => write!(f, "Invalid character: {:?}.", c),
+            Error::ShownDigits(shown, empty) => write!(
+                f,
+                "{} digits to place can't be shown with {} empty cells.",
+                shown, empty
+            ),
         }
     }
 }
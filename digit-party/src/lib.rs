@@ -2,5 +2,5 @@ mod agent;
 pub mod error;
 mod state;
 
-pub use agent::{Agent, MonteCarloAgent, RandomAgent};
+pub use agent::{Agent, MonteCarloAgent, MonteCarloTimeBudget, RandomAgent};
 pub use state::GameState;
@@ -1,26 +1,18 @@
-use digit_party::{Agent, MonteCarloAgent};
-use petgraph::graph::node_index;
+use digit_party::{Agent, MonteCarloAgent, MonteCarloTimeBudget, RandomAgent};
 
-fn main() {
-    let mut agent = MonteCarloAgent::default();
-    // println!("{:?}", agent);
-
-    // let root = agent.graph.node_indices().find(|_| true).unwrap();
-    let root = node_index(0);
-
-    agent.expand(root);
-    // println!("{:?}", agent);
-
-    // let best = agent.select(2.0);
-    // println!("{:?}", best);
+fn average_score(agent: &mut impl Agent, games: usize) -> f64 {
+    let total = agent
+        .play_many(games)
+        .iter()
+        .map(|game| game.score())
+        .sum::<u32>();
+    total as f64 / games as f64
+}
 
-    let games = agent.play_many(10_000);
-    let scores = games.iter().map(|game| game.score()).collect::<Vec<_>>();
-    let average = scores.iter().fold((0, 0), |mut total, score| {
-        total.0 += score;
-        total.1 += 1;
-        total
-    });
+fn main() {
+    let mut random = RandomAgent::default();
+    println!("random: {:.2}", average_score(&mut random, 10_000));
 
-    println!("{:?}", (average.0 as f64) / (average.1 as f64));
+    let mut mcts = MonteCarloAgent::new(MonteCarloTimeBudget::Iterations(5_000));
+    println!("mcts:   {:.2}", average_score(&mut mcts, 20));
 }
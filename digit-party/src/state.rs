@@ -1,9 +1,14 @@
+use crate::error::Error;
+use common::collections;
 use nanorand::{Rng, WyRand};
+use std::{collections::HashMap, str::FromStr};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct GameState {
     pub seed: u64,
     turn: usize,
+    /// how many digits, counting from the first, the player has been shown so far
+    revealed: usize,
     digits: [u8; 25],
     board: [Option<u8>; 25],
 }
@@ -23,11 +28,23 @@ impl GameState {
         Self {
             seed,
             turn: 0,
+            revealed: 2,
             digits,
             board: [None; 25],
         }
     }
 
+    /// The game as the player sees it: the current and next digits, but none of the digits still
+    /// to come, nor the seed that would generate them.
+    pub fn observed(&self) -> Self {
+        let mut out = self.clone();
+        out.seed = 0;
+        for digit in out.digits.iter_mut().skip(self.revealed) {
+            *digit = 0;
+        }
+        out
+    }
+
     pub fn score(&self) -> u32 {
         CONNECTIONS
             .map(|(a, b)| match (self.board[a], self.board[b]) {
@@ -51,6 +68,13 @@ impl GameState {
     }
 
     pub fn step(&self, idx: usize) -> Self {
+        let mut out = self.place(idx);
+        out.revealed = out.revealed.max(out.turn + 2).min(25);
+        out
+    }
+
+    /// Places the current digit without drawing the one after next.
+    fn place(&self, idx: usize) -> Self {
         let mut out = self.clone();
         out.turn += 1;
         out.board[idx] = Some(self.digit_current());
@@ -75,12 +99,70 @@ impl Default for GameState {
     }
 }
 
+/// Digit Party as a single-player game for the shared search. Placing a digit is a decision, and
+/// drawing the digit after next is a chance event with each of the nine digits equally likely,
+/// so a search started from `GameState::observed` never relies on digits it hasn't been shown.
+impl collections::GameState for GameState {
+    type Reward = u32;
+    type Action = usize;
+    type Player = ();
+    type ActionIter = std::vec::IntoIter<(Self::Player, Self::Action)>;
+    type Error = Error;
+
+    fn current_player(&self) -> Self::Player {}
+
+    fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
+        HashMap::from([((), self.score())])
+    }
+
+    fn is_complete(&self) -> bool {
+        GameState::is_complete(self)
+    }
+
+    fn action_iter(&self) -> Self::ActionIter {
+        if collections::GameState::is_chance(self) {
+            return Vec::new().into_iter();
+        }
+        self.open_indices()
+            .map(|idx| ((), idx))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn step(self, _: &Self::Player, action: &Self::Action) -> Result<Self, Self::Error> {
+        if collections::GameState::is_chance(&self) {
+            let digit = u8::try_from(*action)
+                .ok()
+                .filter(|digit| (1..=9).contains(digit))
+                .ok_or(Error::InvalidDigit(*action))?;
+            let mut out = self;
+            out.digits[out.revealed] = digit;
+            out.revealed += 1;
+            Ok(out)
+        } else if self.board.get(*action) == Some(&None) {
+            Ok(self.place(*action))
+        } else {
+            Err(Error::InvalidAction(*action))
+        }
+    }
+
+    fn is_chance(&self) -> bool {
+        !GameState::is_complete(self) && self.revealed < (self.turn + 2).min(25)
+    }
+
+    fn chance_outcomes(&self) -> Vec<(Self::Player, Self::Action, f64)> {
+        (1..=9).map(|digit| ((), digit, 1.0 / 9.0)).collect()
+    }
+}
+
+/// Draws the board in a frame, followed while the game is in progress by a `digits:` line with
+/// the digits shown but not yet placed, current first.
 impl std::fmt::Display for GameState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "+-------------------+\n")?;
-        write!(
+        writeln!(f, "+-------------------+")?;
+        writeln!(
             f,
-            "| {} | {} | {} | {} | {} |\n",
+            "| {} | {} | {} | {} | {} |",
             self.board[0]
                 .map(|x| x.to_string())
                 .unwrap_or(String::from(" ")),
@@ -97,9 +179,9 @@ impl std::fmt::Display for GameState {
                 .map(|x| x.to_string())
                 .unwrap_or(String::from(" ")),
         )?;
-        write!(
+        writeln!(
             f,
-            "| {} | {} | {} | {} | {} |\n",
+            "| {} | {} | {} | {} | {} |",
             self.board[5]
                 .map(|x| x.to_string())
                 .unwrap_or(String::from(" ")),
@@ -116,9 +198,9 @@ impl std::fmt::Display for GameState {
                 .map(|x| x.to_string())
                 .unwrap_or(String::from(" ")),
         )?;
-        write!(
+        writeln!(
             f,
-            "| {} | {} | {} | {} | {} |\n",
+            "| {} | {} | {} | {} | {} |",
             self.board[10]
                 .map(|x| x.to_string())
                 .unwrap_or(String::from(" ")),
@@ -135,9 +217,9 @@ impl std::fmt::Display for GameState {
                 .map(|x| x.to_string())
                 .unwrap_or(String::from(" ")),
         )?;
-        write!(
+        writeln!(
             f,
-            "| {} | {} | {} | {} | {} |\n",
+            "| {} | {} | {} | {} | {} |",
             self.board[15]
                 .map(|x| x.to_string())
                 .unwrap_or(String::from(" ")),
@@ -154,9 +236,9 @@ impl std::fmt::Display for GameState {
                 .map(|x| x.to_string())
                 .unwrap_or(String::from(" ")),
         )?;
-        write!(
+        writeln!(
             f,
-            "| {} | {} | {} | {} | {} |\n",
+            "| {} | {} | {} | {} | {} |",
             self.board[20]
                 .map(|x| x.to_string())
                 .unwrap_or(String::from(" ")),
@@ -173,7 +255,91 @@ impl std::fmt::Display for GameState {
                 .map(|x| x.to_string())
                 .unwrap_or(String::from(" ")),
         )?;
-        write!(f, "+-------------------+\n")
+        writeln!(f, "+-------------------+")?;
+        // the digits the player has been shown but not yet placed, current first
+        if !self.is_complete() {
+            let shown = self.digits[self.turn..self.revealed]
+                .iter()
+                .map(|digit| digit.to_string())
+                .collect::<Vec<_>>();
+            writeln!(f, "digits: {}", shown.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads back the game printed by `Display`: the board and the digits shown but not yet placed.
+/// The rendering doesn't say which order the placed digits went down in, so they are taken in
+/// board order, and the digits nobody has seen yet are dealt as if from seed 0.
+impl FromStr for GameState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const BORDER: &str = "+-------------------+";
+
+        let lines = s.lines().collect::<Vec<_>>();
+        if lines.len() != 7 && lines.len() != 8 {
+            return Err(Error::WrongLineCount(lines.len()));
+        }
+        for idx in [0, 6] {
+            if lines[idx] != BORDER {
+                return Err(Error::MalformedLine(idx));
+            }
+        }
+
+        let mut board = [None; 25];
+        for (row, line) in lines[1..6].iter().enumerate() {
+            let chars = line.chars().collect::<Vec<_>>();
+            if chars.len() != 21 || chars[0] != '|' {
+                return Err(Error::MalformedLine(row + 1));
+            }
+            for (col, cell) in chars[1..].chunks(4).enumerate() {
+                let [' ', c, ' ', '|'] = cell else {
+                    return Err(Error::MalformedLine(row + 1));
+                };
+                board[row * 5 + col] = match c {
+                    ' ' => None,
+                    '1'..='9' => c.to_digit(10).map(|digit| digit as u8),
+                    c => return Err(Error::InvalidCharacter(*c)),
+                };
+            }
+        }
+
+        let shown = match lines.get(7) {
+            Some(line) => {
+                let digits = line
+                    .strip_prefix("digits: ")
+                    .ok_or(Error::MalformedLine(7))?;
+                digits
+                    .split(' ')
+                    .map(|digit| match digit.parse::<u8>() {
+                        Ok(digit @ 1..=9) => Ok(digit),
+                        _ => Err(Error::MalformedLine(7)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            None => Vec::new(),
+        };
+
+        let placed = board.iter().flatten().copied().collect::<Vec<_>>();
+        // a game in progress has shown the current digit, and the next one if there is a cell
+        // left for it; a finished game shows none
+        let empty = 25 - placed.len();
+        let valid = match empty {
+            0 => shown.is_empty(),
+            _ => (1..=empty.min(2)).contains(&shown.len()),
+        };
+        if !valid {
+            return Err(Error::ShownDigits(shown.len(), empty));
+        }
+
+        let mut out = Self::seed_from_u64(0);
+        out.digits[..placed.len()].copy_from_slice(&placed);
+        out.digits[placed.len()..placed.len() + shown.len()].copy_from_slice(&shown);
+        out.turn = placed.len();
+        out.revealed = out.turn + shown.len();
+        out.board = board;
+        Ok(out)
     }
 }
 
@@ -253,3 +419,103 @@ const CONNECTIONS: [(usize, usize); 72] = [
     (18, 22),
     (19, 23),
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::collections::GameState as _;
+
+    #[test]
+    fn observed_hides_future_digits() {
+        let game = GameState::seed_from_u64(3);
+        let observed = game.observed();
+        assert_eq!(observed.digit_current(), game.digit_current());
+        assert_eq!(observed.digit_next(), game.digit_next());
+        assert!(observed.digits[2..].iter().all(|&digit| digit == 0));
+    }
+
+    #[test]
+    fn drawing_a_digit_is_a_chance_event() {
+        let game = GameState::seed_from_u64(3);
+        let placed = game.observed().step(&(), &12).unwrap();
+        assert!(placed.is_chance());
+        assert!(placed.action_iter().next().is_none());
+
+        let drawn = placed.step(&(), &(game.digits[2] as usize)).unwrap();
+        assert_eq!(drawn, GameState::step(&game, 12).observed());
+        assert!(!drawn.is_chance());
+        assert!(drawn.clone().step(&(), &12).is_err());
+    }
+
+    #[test]
+    fn parse_printed_game() {
+        let mut game = GameState::seed_from_u64(5);
+        for idx in [12, 0, 24, 7, 6] {
+            game = GameState::step(&game, idx);
+        }
+        let parsed = game.to_string().parse::<GameState>().unwrap();
+        assert_eq!(parsed.to_string(), game.to_string());
+        assert_eq!(parsed.score(), game.score());
+        assert_eq!(parsed.open_indices().count(), 20);
+        assert_eq!(parsed.digit_current(), game.digit_current());
+        assert_eq!(parsed.digit_next(), game.digit_next());
+        assert!(!parsed.is_chance());
+
+        // an observed game waiting on a draw only shows the current digit
+        let waiting = game.observed().step(&(), &1).unwrap();
+        assert!(waiting.is_chance());
+        let parsed = waiting.to_string().parse::<GameState>().unwrap();
+        assert_eq!(parsed.to_string(), waiting.to_string());
+        assert_eq!(parsed.digit_current(), waiting.digit_current());
+        assert!(parsed.is_chance());
+
+        // a finished game shows no digits
+        let done = game
+            .open_indices()
+            .fold(game.clone(), |done, idx| GameState::step(&done, idx));
+        let parsed = done.to_string().parse::<GameState>().unwrap();
+        assert_eq!(parsed.to_string(), done.to_string());
+        assert_eq!(parsed.score(), done.score());
+
+        let text = game.to_string();
+        assert!(matches!(
+            text.replacen("|   |", "| 0 |", 1).parse::<GameState>(),
+            Err(Error::InvalidCharacter('0'))
+        ));
+        assert!(matches!(
+            text.replacen('+', "-", 1).parse::<GameState>(),
+            Err(Error::MalformedLine(0))
+        ));
+        assert!(matches!(
+            format!("{}\n", text).parse::<GameState>(),
+            Err(Error::WrongLineCount(9))
+        ));
+        assert!(matches!(
+            "".parse::<GameState>(),
+            Err(Error::WrongLineCount(0))
+        ));
+        let current = game.digit_current();
+        let next = game.digit_next().unwrap();
+        let digits = format!("digits: {} {}", current, next);
+        for (shown, error) in [
+            (String::from("digits: "), Error::MalformedLine(7)),
+            (format!("digits: {} 0", current), Error::MalformedLine(7)),
+            (format!("{} 1", digits), Error::ShownDigits(3, 20)),
+        ] {
+            let result = text.replace(&digits, &shown).parse::<GameState>();
+            assert_eq!(
+                result.map_err(|error| error.to_string()),
+                Err(error.to_string())
+            );
+        }
+        assert!(matches!(
+            text.replace(&format!("{}\n", digits), "")
+                .parse::<GameState>(),
+            Err(Error::ShownDigits(0, 20))
+        ));
+        assert!(matches!(
+            format!("{}digits: 1\n", done).parse::<GameState>(),
+            Err(Error::ShownDigits(1, 0))
+        ));
+    }
+}
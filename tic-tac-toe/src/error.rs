@@ -5,6 +5,22 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[derive(Debug)]
 pub enum Error {
     InvalidAction(Player, usize),
+    /// a board and cell of a multi-board game that can't be played.
+    InvalidBoardAction(Player, usize, usize),
+    /// a multi-board game with no boards to play on.
+    NoBoards,
+    /// a line of a printed game, counting from zero, that doesn't match the format.
+    MalformedLine(usize),
+    InvalidCharacter(char),
+    /// X and O counts that can't come from alternating turns.
+    PieceCount(usize, usize),
+    /// rows, columns and line length that don't make a playable m,n,k-game.
+    InvalidDimensions(usize, usize, usize),
+    WrongPlayer(Player),
+    GameOver,
+    /// choosing stones, or placing the two extra swap2 stones, when the opening doesn't call for
+    /// it.
+    InvalidChoice,
 }
 
 impl std::error::Error for Error {}
@@ -13,6 +29,25 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::InvalidAction(player, i) => write!(f, "{:?} cannot play at index {}", player, i),
+            Self::InvalidBoardAction(player, board, i) => {
+                write!(
+                    f,
+                    "{:?} cannot play at index {} of board {}",
+                    player, i, board
+                )
+            }
+            Self::NoBoards => write!(f, "a game needs at least one board"),
+            Self::MalformedLine(line) => write!(f, "line {} is not part of a board", line + 1),
+            Self::InvalidCharacter(c) => write!(f, "{:?} is not X, O or a space", c),
+            Self::PieceCount(x, o) => {
+                write!(f, "{} Xs and {} Os can't come from alternating turns", x, o)
+            }
+            Self::InvalidDimensions(rows, cols, k) => {
+                write!(f, "a {}x{} board can't hold a line of {}", rows, cols, k)
+            }
+            Self::WrongPlayer(player) => write!(f, "it is not {:?}'s turn", player),
+            Self::GameOver => write!(f, "the game is already over"),
+            Self::InvalidChoice => write!(f, "the opening doesn't allow that choice now"),
         }
     }
 }
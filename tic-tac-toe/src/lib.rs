@@ -1,16 +1,36 @@
-use common::collections::GameState;
-use std::collections::HashMap;
+use common::{
+    collections::{GameResult, GameState},
+    traits::Symmetric,
+};
+use std::{collections::HashMap, str::FromStr};
 
 pub mod error;
+mod mnk;
+mod notakto;
+mod solver;
 
 use error::Error;
+pub use mnk::{MnkAction, MnkGame, Opening};
+pub use notakto::Notakto;
+pub use solver::{PerfectAgent, Solution, Solver};
 
-#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Hash, Clone, Copy, PartialEq, Eq)]
 pub enum Player {
+    #[default]
     X,
     O,
 }
 
+impl Player {
+    /// The player who moves after this one.
+    pub fn opponent(self) -> Self {
+        match self {
+            Self::X => Self::O,
+            Self::O => Self::X,
+        }
+    }
+}
+
 impl From<Player> for char {
     fn from(player: Player) -> Self {
         match player {
@@ -29,16 +49,32 @@ impl std::fmt::Display for Player {
     }
 }
 
-impl Default for Player {
-    fn default() -> Self {
-        Self::X
-    }
+/// How a game stands: won by one of the players, drawn on a full board, or still being played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Win(Player),
+    Draw,
+    InProgress,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// Which three in a row counts, and for whom.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rules {
+    /// Three in a row wins.
+    #[default]
+    Standard,
+    /// Three of your own in a row loses.
+    Misere,
+    /// Both players place X, and whoever completes a line of three loses. Notakto boards can't
+    /// be drawn: the ninth X always completes a line, if nothing before it did.
+    Notakto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Game {
     board: [Option<Player>; 9],
     current_player: Player,
+    rules: Rules,
 }
 
 impl Game {
@@ -46,6 +82,45 @@ impl Game {
         Self {
             board,
             current_player,
+            rules: Rules::Standard,
+        }
+    }
+
+    /// Plays by `rules` instead of the standard ones.
+    pub fn with_rules(self, rules: Rules) -> Self {
+        Self { rules, ..self }
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// The nine cells, row by row from the top left.
+    pub fn board(&self) -> &[Option<Player>; 9] {
+        &self.board
+    }
+
+    /// The result under the game's rules. Under misère and Notakto rules, `Win` names the player
+    /// who avoided making the line.
+    pub fn outcome(&self) -> Outcome {
+        let line = THREES.into_iter().find_map(|[a, b, c]| {
+            if self.board[a].is_some()
+                && self.board[a] == self.board[b]
+                && self.board[b] == self.board[c]
+            {
+                self.board[a]
+            } else {
+                None
+            }
+        });
+
+        match (line, self.rules) {
+            (Some(player), Rules::Standard) => Outcome::Win(player),
+            (Some(player), Rules::Misere) => Outcome::Win(player.opponent()),
+            // every line is made of Xs, so the loser is whoever moved last
+            (Some(_), Rules::Notakto) => Outcome::Win(self.current_player),
+            (None, _) if self.board.iter().all(Option::is_some) => Outcome::Draw,
+            (None, _) => Outcome::InProgress,
         }
     }
 
@@ -69,31 +144,26 @@ impl GameState for Game {
         self.current_player
     }
 
+    /// A point for the winner, or half a point each for a draw. A search can value draws
+    /// differently with `MonteCarloTree::with_reward_map`.
     fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
-        let mut winner = THREES.into_iter().filter_map(|[a, b, c]| {
-            if self.board[a].is_some()
-                && self.board[a] == self.board[b]
-                && self.board[b] == self.board[c]
-            {
-                self.board[a]
-            } else {
-                None
-            }
-        });
-
-        if let Some(player) = winner.next() {
-            HashMap::from([(player, 1.0)])
-        } else {
-            HashMap::default()
+        match self.outcome() {
+            Outcome::Win(player) => HashMap::from([(player, 1.0)]),
+            Outcome::Draw => HashMap::from([(Player::X, 0.5), (Player::O, 0.5)]),
+            Outcome::InProgress => HashMap::default(),
         }
     }
 
     fn is_complete(&self) -> bool {
-        THREES.into_iter().any(|[a, b, c]| {
-            self.board[a].is_some()
-                && self.board[a] == self.board[b]
-                && self.board[b] == self.board[c]
-        })
+        self.outcome() != Outcome::InProgress
+    }
+
+    fn result(&self) -> Option<GameResult<Self::Player>> {
+        match self.outcome() {
+            Outcome::Win(player) => Some(GameResult::Win(player)),
+            Outcome::Draw => Some(GameResult::Draw),
+            Outcome::InProgress => None,
+        }
     }
 
     fn step(
@@ -103,21 +173,24 @@ impl GameState for Game {
     ) -> std::prelude::v1::Result<Self, Self::Error> {
         let mut board = self.board;
         if board[*action].is_some() {
-            Err(Error::InvalidAction(player.clone(), *action))
+            Err(Error::InvalidAction(*player, *action))
         } else {
-            board[*action] = Some(*player);
-            let current_player = match self.current_player {
-                Player::X => Player::O,
-                Player::O => Player::X,
+            board[*action] = match self.rules {
+                Rules::Standard | Rules::Misere => Some(*player),
+                Rules::Notakto => Some(Player::X),
             };
             Ok(Self {
                 board,
-                current_player,
+                current_player: self.current_player.opponent(),
+                ..self
             })
         }
     }
 
     fn action_iter(&self) -> Self::ActionIter {
+        if self.is_complete() {
+            return Vec::new().into_iter();
+        }
         self.open_indices()
             .into_iter()
             .map(|ix| (self.current_player, ix))
@@ -126,6 +199,12 @@ impl GameState for Game {
     }
 }
 
+impl Default for Game {
+    fn default() -> Self {
+        Self::new([None; 9], Player::X)
+    }
+}
+
 impl std::fmt::Display for Game {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(
@@ -154,6 +233,94 @@ impl std::fmt::Display for Game {
     }
 }
 
+/// Reads back the grid printed by `Display`. X moves first, so the player to move is X when
+/// both have played equally often and O when X is one move ahead; any other count is an error.
+impl FromStr for Game {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s.lines().collect::<Vec<_>>();
+        if lines.len() != 5 {
+            return Err(Error::MalformedLine(lines.len().min(5)));
+        }
+
+        let mut board = [None; 9];
+        for (idx, line) in lines.iter().enumerate() {
+            if idx % 2 == 1 {
+                if *line != "---+---+---" {
+                    return Err(Error::MalformedLine(idx));
+                }
+                continue;
+            }
+
+            let chars = line.chars().collect::<Vec<_>>();
+            let [' ', a, ' ', '|', ' ', b, ' ', '|', ' ', c] = chars[..] else {
+                return Err(Error::MalformedLine(idx));
+            };
+            for (col, cell) in [a, b, c].into_iter().enumerate() {
+                board[idx / 2 * 3 + col] = match cell {
+                    'X' => Some(Player::X),
+                    'O' => Some(Player::O),
+                    ' ' => None,
+                    c => return Err(Error::InvalidCharacter(c)),
+                };
+            }
+        }
+
+        let count = |player| board.iter().filter(|&&cell| cell == Some(player)).count();
+        let current_player = match (count(Player::X), count(Player::O)) {
+            (x, o) if x == o => Player::X,
+            (x, o) if x == o + 1 => Player::O,
+            (x, o) => return Err(Error::PieceCount(x, o)),
+        };
+        Ok(Self::new(board, current_player))
+    }
+}
+
+/// Rotations and reflections map one board onto another, so a game has up to eight orientations.
+/// The canonical one has the lowest cells when read as a base-3 number, empty being zero, X one
+/// and O two.
+impl Symmetric for Game {
+    fn symmetries(&self) -> Vec<Self> {
+        SYMMETRIES
+            .into_iter()
+            .map(|perm| Self {
+                board: perm.map(|idx| self.board[idx]),
+                ..*self
+            })
+            .collect()
+    }
+
+    fn canonical(&self) -> Self {
+        self.symmetries()
+            .into_iter()
+            .min_by_key(|game| {
+                game.board.iter().fold(0, |code, cell| {
+                    3 * code
+                        + match cell {
+                            None => 0,
+                            Some(Player::X) => 1,
+                            Some(Player::O) => 2,
+                        }
+                })
+            })
+            .unwrap()
+    }
+}
+
+/// where each cell comes from under every symmetry of the board: the identity, the three
+/// clockwise rotations, the horizontal and vertical mirrors and the two diagonal flips.
+const SYMMETRIES: [[usize; 9]; 8] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8],
+    [6, 3, 0, 7, 4, 1, 8, 5, 2],
+    [8, 7, 6, 5, 4, 3, 2, 1, 0],
+    [2, 5, 8, 1, 4, 7, 0, 3, 6],
+    [2, 1, 0, 5, 4, 3, 8, 7, 6],
+    [6, 7, 8, 3, 4, 5, 0, 1, 2],
+    [0, 3, 6, 1, 4, 7, 2, 5, 8],
+    [8, 5, 2, 7, 4, 1, 6, 3, 0],
+];
+
 const THREES: [[usize; 3]; 8] = [
     [0, 1, 2],
     [3, 4, 5],
@@ -191,5 +358,133 @@ mod tests {
         ];
         let game = Game::new(board, Player::O);
         assert!(game.is_complete());
+        assert_eq!(game.outcome(), Outcome::Win(Player::X));
+        assert_eq!(game.action_iter().count(), 0);
+    }
+
+    #[test]
+    fn full_board_is_a_draw() {
+        // X O X / X O O / O X X
+        let game = [0, 1, 2, 4, 3, 5, 7, 6, 8]
+            .into_iter()
+            .try_fold(Game::default(), |game, idx| {
+                let player = game.current_player();
+                game.step(&player, &idx)
+            })
+            .unwrap();
+        assert_eq!(game.outcome(), Outcome::Draw);
+        assert!(game.is_complete());
+        assert_eq!(
+            game.reward(),
+            HashMap::from([(Player::X, 0.5), (Player::O, 0.5)])
+        );
+
+        assert_eq!(Game::default().outcome(), Outcome::InProgress);
+    }
+
+    #[test]
+    fn proves_draws_whatever_they_pay() {
+        use common::collections::{MonteCarloTree, SearchBudget, StopReason, Ucb1};
+
+        // X O X / X O O / O X _, where X's last move draws
+        let game = [0, 1, 2, 4, 3, 5, 7, 6]
+            .into_iter()
+            .try_fold(Game::default(), |game, idx| {
+                let player = game.current_player();
+                game.step(&player, &idx)
+            })
+            .unwrap();
+        let mut tree = MonteCarloTree::seed_from_state(0, game)
+            .with_solver()
+            .with_reward_map(|reward| reward.into_keys().map(|player| (player, 0.0)).collect());
+        let summary = tree.search(&Ucb1::default(), &SearchBudget::new().iterations(100));
+        assert_eq!(summary.stop, StopReason::Solved);
+        assert!(tree.root_proof().unwrap().is_draw());
+    }
+
+    #[test]
+    fn parse_printed_game() {
+        let game = Game::default()
+            .step(&Player::X, &4)
+            .and_then(|game| game.step(&Player::O, &0))
+            .and_then(|game| game.step(&Player::X, &8))
+            .unwrap();
+        assert_eq!(game.to_string().parse::<Game>().unwrap(), game);
+        assert_eq!(
+            Game::default().to_string().parse::<Game>().unwrap(),
+            Game::default()
+        );
+
+        let text = game.to_string();
+        assert!(matches!(
+            text.replacen('O', "X", 1).parse::<Game>(),
+            Err(Error::PieceCount(3, 0))
+        ));
+        assert!(matches!(
+            text.replacen('O', "Q", 1).parse::<Game>(),
+            Err(Error::InvalidCharacter('Q'))
+        ));
+        assert!(matches!(
+            text.replacen("---+---+---", "-----------", 1)
+                .parse::<Game>(),
+            Err(Error::MalformedLine(1))
+        ));
+    }
+
+    #[test]
+    fn rules_decide_who_a_line_favors() {
+        // X completes the top row
+        let play = |rules| {
+            [0, 3, 1, 4, 2]
+                .into_iter()
+                .try_fold(Game::default().with_rules(rules), |game, idx| {
+                    let player = game.current_player();
+                    game.step(&player, &idx)
+                })
+                .unwrap()
+        };
+        let standard = play(Rules::Standard);
+        assert_eq!(standard.rules(), Rules::Standard);
+        assert_eq!(standard.reward(), HashMap::from([(Player::X, 1.0)]));
+
+        let misere = play(Rules::Misere);
+        assert_eq!(misere.outcome(), Outcome::Win(Player::O));
+        assert_eq!(misere.reward(), HashMap::from([(Player::O, 1.0)]));
+        assert_ne!(misere, standard);
+
+        // in Notakto every cell is an X, so the top row is made on the fifth move, by X
+        let notakto = play(Rules::Notakto);
+        assert!(notakto.board()[..5]
+            .iter()
+            .all(|&cell| cell == Some(Player::X)));
+        assert_eq!(notakto.outcome(), Outcome::Win(Player::O));
+    }
+
+    #[test]
+    fn canonical_forms() {
+        let corners =
+            [0, 2, 6, 8].map(|idx| Game::default().step(&Player::X, &idx).unwrap().canonical());
+        assert!(corners.iter().all(|game| *game == corners[0]));
+        assert!(corners[0].is_canonical());
+
+        let edge = Game::default().step(&Player::X, &1).unwrap();
+        assert_ne!(edge.canonical(), corners[0]);
+        assert_eq!(edge.symmetries().len(), 8);
+        assert_eq!(edge.symmetries()[0], edge);
+
+        // every orientation of a game shares its canonical form and its outcome
+        let game = [0, 1, 4, 8]
+            .into_iter()
+            .try_fold(Game::default(), |game, idx| {
+                let player = game.current_player();
+                game.step(&player, &idx)
+            })
+            .unwrap();
+        for variant in game.symmetries() {
+            assert_eq!(variant.canonical(), game.canonical());
+            assert_eq!(variant.outcome(), game.outcome());
+            assert_eq!(variant.current_player(), Player::X);
+        }
+        assert_eq!(Game::default().canonical(), Game::default());
     }
 }
@@ -1,25 +1,48 @@
-use common::collections::MonteCarloTree;
-use petgraph::Direction;
-use tic_tac_toe::{Game, Player};
+use common::collections::{GameState, MonteCarloTree, MoveSelection, SearchBudget, Ucb1};
+use tic_tac_toe::{Game, Player, Solver};
 
 fn main() {
-    let mut tree = MonteCarloTree::<Game>::new();
-    for _ in 0..800000 {
-        tree.iterate(1.2);
-    }
+    // X opens in the center, and O has to answer in a corner: every edge loses
+    let game = Game::default().step(&Player::X, &4).unwrap();
+    let solver = Solver::new();
+    let optimal = solver.optimal_moves(&game).unwrap();
+    assert!(optimal.len() < game.open_indices().len());
+
+    let mut tree = MonteCarloTree::seed_from_state(0, game);
+    let summary = tree.search(&Ucb1::new(1.2), &SearchBudget::new().iterations(800000));
+    println!("{:?}", summary);
 
     let root = tree.graph.node_weight(tree.root).unwrap();
     println!("{:?}", root);
-    for child in tree
-        .graph
-        .neighbors_directed(tree.root, Direction::Outgoing)
-    {
-        println!("{:?}", tree.graph.node_weight(child).unwrap());
-    }
 
-    // for node in tree.graph.node_indices() {
-    //     println!("{:?} : {:?}", node, tree.graph.node_weight(node).unwrap());
-    // }
+    let best = tree.best_action(MoveSelection::RobustChild).unwrap();
+    let mut mistakes = 0;
+    for child in best.children.iter() {
+        let is_optimal = optimal.contains(&child.action);
+        if !is_optimal {
+            mistakes += child.visits;
+        }
+        println!(
+            "{} plays {}: {} visits, mean reward {:.3}, {}",
+            child.player,
+            child.action,
+            child.visits,
+            child.mean_reward,
+            if is_optimal { "optimal" } else { "a mistake" }
+        );
+    }
+    println!("{} should play {}", best.player, best.action);
 
-    // println!("{:?}", tree);
+    let visits = best.children.iter().map(|child| child.visits).sum::<u32>();
+    assert!(
+        optimal.contains(&best.action),
+        "the search picked {}, a mistake",
+        best.action
+    );
+    assert!(
+        2 * mistakes < visits,
+        "mistakes got {} of {} visits",
+        mistakes,
+        visits
+    );
 }
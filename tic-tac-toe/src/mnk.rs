@@ -0,0 +1,568 @@
+use crate::{
+    error::{Error, Result},
+    Player,
+};
+use common::collections::{GameResult, GameState};
+use std::collections::HashMap;
+
+/// the four directions a line can run in, as (row, column) steps: across, down and both
+/// diagonals. each is also walked backwards, so these cover every line through a cell.
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+/// How a game starts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Opening {
+    /// X moves first and the players alternate from the start.
+    #[default]
+    Free,
+    /// The gomoku tournament opening. Player X places two X stones and an O stone. Player O
+    /// then either picks which stones to play, or places another O stone and X stone and leaves
+    /// the choice to player X. Whoever holds the O stones moves next.
+    Swap2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MnkAction {
+    /// put the next stone on the cell at this index, counting row by row from the top left.
+    Place(usize),
+    /// play the given stones for the rest of the game.
+    Choose(Player),
+    /// place one more stone of each kind and let the other player choose instead.
+    PlaceTwo,
+}
+
+/// where a swap2 opening is up to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Phase {
+    PlaceThree,
+    ChooseAfterThree,
+    PlaceTwo,
+    ChooseAfterFive,
+    Play,
+}
+
+/// A game of m,n,k: two players take turns placing stones on an `rows` by `cols` grid, and the
+/// first to get `k` in a row across, down or diagonally wins. Tic-tac-toe is 3,3,3 and gomoku is
+/// 15,15,5, the default.
+///
+/// Stones are X and O, and X stones are always placed first. `Player` also names the two
+/// players, and they play the stones of the same name unless a swap2 opening says otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MnkGame {
+    rows: usize,
+    cols: usize,
+    k: usize,
+    /// when set, a line of more than `k` stones doesn't count.
+    exact: bool,
+    cells: Vec<Option<Player>>,
+    /// the player holding the X stones.
+    x_player: Player,
+    phase: Phase,
+    last_move: Option<usize>,
+    /// the stones that made a line, found from the last move when it was placed.
+    winner: Option<Player>,
+    stones: usize,
+}
+
+impl MnkGame {
+    pub fn new(rows: usize, cols: usize, k: usize) -> Result<Self> {
+        if rows == 0 || cols == 0 || k == 0 || k > rows.max(cols) {
+            return Err(Error::InvalidDimensions(rows, cols, k));
+        }
+        Ok(Self {
+            rows,
+            cols,
+            k,
+            exact: false,
+            cells: vec![None; rows * cols],
+            x_player: Player::X,
+            phase: Phase::Play,
+            last_move: None,
+            winner: None,
+            stones: 0,
+        })
+    }
+
+    /// Only lines of exactly `k` win, as in standard gomoku, where six in a row doesn't count.
+    pub fn with_exact_k(self, exact: bool) -> Self {
+        Self { exact, ..self }
+    }
+
+    /// Starts the game with `opening`. Only has an effect on an empty board.
+    pub fn with_opening(self, opening: Opening) -> Self {
+        if self.stones > 0 {
+            return self;
+        }
+        let phase = match opening {
+            Opening::Free => Phase::Play,
+            Opening::Swap2 => Phase::PlaceThree,
+        };
+        Self { phase, ..self }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The stone on the cell at `row`, `col`.
+    pub fn get(&self, row: usize, col: usize) -> Option<Player> {
+        if row < self.rows && col < self.cols {
+            self.cells[row * self.cols + col]
+        } else {
+            None
+        }
+    }
+
+    pub fn last_move(&self) -> Option<usize> {
+        self.last_move
+    }
+
+    /// The stones that go down next: X when both have been placed equally often.
+    pub fn next_stone(&self) -> Player {
+        if self.stones.is_multiple_of(2) {
+            Player::X
+        } else {
+            Player::O
+        }
+    }
+
+    /// The player playing `stone`.
+    pub fn player_of(&self, stone: Player) -> Player {
+        if stone == Player::X {
+            self.x_player
+        } else {
+            self.x_player.opponent()
+        }
+    }
+
+    /// The player who has won, if any.
+    pub fn winner(&self) -> Option<Player> {
+        self.winner.map(|stone| self.player_of(stone))
+    }
+
+    /// Whether the opening is still being decided.
+    pub fn in_opening(&self) -> bool {
+        self.phase != Phase::Play
+    }
+
+    pub fn open_indices(&self) -> Vec<usize> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| if v.is_none() { Some(i) } else { None })
+            .collect()
+    }
+
+    /// Every action open to the player to move.
+    pub fn legal_actions(&self) -> Vec<MnkAction> {
+        if self.is_complete() {
+            return Vec::new();
+        }
+        match self.phase {
+            Phase::ChooseAfterThree => vec![
+                MnkAction::Choose(Player::X),
+                MnkAction::Choose(Player::O),
+                MnkAction::PlaceTwo,
+            ],
+            Phase::ChooseAfterFive => {
+                vec![MnkAction::Choose(Player::X), MnkAction::Choose(Player::O)]
+            }
+            Phase::PlaceThree | Phase::PlaceTwo | Phase::Play => self
+                .open_indices()
+                .into_iter()
+                .map(MnkAction::Place)
+                .collect(),
+        }
+    }
+
+    /// Plays `action` for the player to move.
+    pub fn update(self, action: MnkAction) -> Result<Self> {
+        if self.is_complete() {
+            return Err(Error::GameOver);
+        }
+        match (self.phase, action) {
+            (Phase::ChooseAfterThree | Phase::ChooseAfterFive, MnkAction::Choose(stone)) => {
+                let chooser = self.current_player();
+                let x_player = if stone == Player::X {
+                    chooser
+                } else {
+                    chooser.opponent()
+                };
+                Ok(Self {
+                    x_player,
+                    phase: Phase::Play,
+                    ..self
+                })
+            }
+            (Phase::ChooseAfterThree, MnkAction::PlaceTwo) => Ok(Self {
+                phase: Phase::PlaceTwo,
+                ..self
+            }),
+            (Phase::PlaceThree | Phase::PlaceTwo | Phase::Play, MnkAction::Place(idx)) => {
+                self.place(idx)
+            }
+            _ => Err(Error::InvalidChoice),
+        }
+    }
+
+    fn place(mut self, idx: usize) -> Result<Self> {
+        let stone = self.next_stone();
+        if self.cells.get(idx) != Some(&None) {
+            return Err(Error::InvalidAction(self.current_player(), idx));
+        }
+
+        self.cells[idx] = Some(stone);
+        self.stones += 1;
+        self.last_move = Some(idx);
+        if self.completes_line(idx) {
+            self.winner = Some(stone);
+        }
+        self.phase = match (self.phase, self.stones) {
+            (Phase::PlaceThree, 3) => Phase::ChooseAfterThree,
+            (Phase::PlaceTwo, 5) => Phase::ChooseAfterFive,
+            (phase, _) => phase,
+        };
+        Ok(self)
+    }
+
+    /// whether the stone at `idx` is part of a winning line. only lines through the last stone
+    /// can have changed, so this is all a move needs to check.
+    fn completes_line(&self, idx: usize) -> bool {
+        let (row, col) = (idx / self.cols, idx % self.cols);
+        let stone = self.cells[idx];
+        DIRECTIONS.into_iter().any(|(dr, dc)| {
+            let run = |sign: isize| {
+                (1..)
+                    .take_while(|&step| {
+                        let r = row as isize + sign * dr * step;
+                        let c = col as isize + sign * dc * step;
+                        r >= 0
+                            && c >= 0
+                            && (r as usize) < self.rows
+                            && (c as usize) < self.cols
+                            && self.cells[r as usize * self.cols + c as usize] == stone
+                    })
+                    .count()
+            };
+            let length = 1 + run(1) + run(-1);
+            if self.exact {
+                length == self.k
+            } else {
+                length >= self.k
+            }
+        })
+    }
+}
+
+impl Default for MnkGame {
+    fn default() -> Self {
+        Self::new(15, 15, 5).unwrap()
+    }
+}
+
+impl GameState for MnkGame {
+    type Reward = f64;
+    type Action = MnkAction;
+    type Player = Player;
+    type ActionIter = std::vec::IntoIter<(Self::Player, Self::Action)>;
+    type Error = Error;
+
+    /// The player whose turn it is, whichever stones they hold. During a swap2 opening that is
+    /// player X until the first three stones are down, then player O, who either chooses or
+    /// places two more and hands the choice back to player X.
+    fn current_player(&self) -> Self::Player {
+        match self.phase {
+            Phase::PlaceThree | Phase::ChooseAfterFive => Player::X,
+            Phase::ChooseAfterThree | Phase::PlaceTwo => Player::O,
+            Phase::Play => self.player_of(self.next_stone()),
+        }
+    }
+
+    /// One point to the winner, or half a point each for a full board.
+    fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
+        match self.winner() {
+            Some(player) => HashMap::from([(player, 1.0)]),
+            None if self.stones == self.cells.len() => {
+                HashMap::from([(Player::X, 0.5), (Player::O, 0.5)])
+            }
+            None => HashMap::default(),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.winner.is_some() || self.stones == self.cells.len()
+    }
+
+    fn result(&self) -> Option<GameResult<Self::Player>> {
+        match self.winner() {
+            Some(player) => Some(GameResult::Win(player)),
+            None => self.is_complete().then_some(GameResult::Draw),
+        }
+    }
+
+    fn action_iter(&self) -> Self::ActionIter {
+        let player = self.current_player();
+        self.legal_actions()
+            .into_iter()
+            .map(|action| (player, action))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn step(self, player: &Self::Player, action: &Self::Action) -> Result<Self> {
+        if *player != self.current_player() {
+            return Err(Error::WrongPlayer(*player));
+        }
+        self.update(*action)
+    }
+}
+
+/// The same grid `Game` prints, sized to the board.
+impl std::fmt::Display for MnkGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let separator = vec!["---"; self.cols].join("+");
+        for (idx, row) in self.cells.chunks(self.cols).enumerate() {
+            if idx > 0 {
+                writeln!(f, "{}", separator)?;
+            }
+            let cells = row
+                .iter()
+                .map(|cell| format!(" {:1} ", cell.map_or(' ', char::from)))
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", cells.join("|").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Game, Outcome};
+    use common::collections::{MonteCarloTree, MoveSelection, SearchBudget, Ucb1};
+    use nanorand::{Rng, WyRand};
+
+    fn play(game: MnkGame, cells: &[(usize, usize)]) -> Result<MnkGame> {
+        cells.iter().try_fold(game, |game, &(row, col)| {
+            let idx = row * game.cols() + col;
+            game.update(MnkAction::Place(idx))
+        })
+    }
+
+    #[test]
+    fn rejects_impossible_sizes() {
+        assert!(matches!(
+            MnkGame::new(3, 3, 4),
+            Err(Error::InvalidDimensions(3, 3, 4))
+        ));
+        assert!(MnkGame::new(0, 5, 1).is_err());
+        assert!(MnkGame::new(1, 5, 5).is_ok());
+    }
+
+    #[test]
+    fn matches_tic_tac_toe() {
+        let mut rng = WyRand::new_seed(0);
+        for _ in 0..200 {
+            let mut game = Game::default();
+            let mut mnk = MnkGame::new(3, 3, 3).unwrap();
+            while !game.is_complete() {
+                assert!(!mnk.is_complete());
+                let open = game.open_indices();
+                let idx = open[rng.generate_range(0..open.len())];
+                let player = game.current_player();
+                assert_eq!(mnk.current_player(), player);
+                game = GameState::step(game, &player, &idx).unwrap();
+                mnk = mnk.step(&player, &MnkAction::Place(idx)).unwrap();
+            }
+            assert!(mnk.is_complete());
+            assert_eq!(mnk.reward(), game.reward());
+            match game.outcome() {
+                Outcome::Win(player) => assert_eq!(mnk.winner(), Some(player)),
+                _ => assert_eq!(mnk.winner(), None),
+            }
+        }
+    }
+
+    #[test]
+    fn finds_lines_through_the_last_stone() {
+        // X fills row 7 from column 3, O plays row 8 beneath; X's fifth stone goes in the middle
+        let game = play(
+            MnkGame::default(),
+            &[
+                (7, 3),
+                (8, 3),
+                (7, 4),
+                (8, 4),
+                (7, 6),
+                (8, 6),
+                (7, 7),
+                (8, 8),
+            ],
+        )
+        .unwrap();
+        assert_eq!(game.winner(), None);
+        let game = play(game, &[(7, 5)]).unwrap();
+        assert_eq!(game.winner(), Some(Player::X));
+        assert_eq!(game.last_move(), Some(7 * 15 + 5));
+        assert!(game.is_complete());
+        assert!(matches!(
+            game.update(MnkAction::Place(0)),
+            Err(Error::GameOver)
+        ));
+
+        // an anti-diagonal for O, ending on the board's edge
+        let game = play(
+            MnkGame::default(),
+            &[
+                (7, 7),
+                (0, 14),
+                (7, 8),
+                (1, 13),
+                (7, 9),
+                (2, 12),
+                (6, 6),
+                (3, 11),
+            ],
+        )
+        .unwrap();
+        let game = play(game, &[(5, 5), (4, 10)]).unwrap();
+        assert_eq!(game.winner(), Some(Player::O));
+
+        // four at the end of one row and one at the start of the next don't make five
+        let game = play(
+            MnkGame::default(),
+            &[
+                (0, 11),
+                (5, 0),
+                (0, 12),
+                (5, 1),
+                (0, 13),
+                (5, 3),
+                (0, 14),
+                (5, 4),
+                (1, 0),
+            ],
+        )
+        .unwrap();
+        assert_eq!(game.winner(), None);
+    }
+
+    #[test]
+    fn exact_k_ignores_overlines() {
+        let stones = [
+            (7, 1),
+            (9, 1),
+            (7, 2),
+            (9, 2),
+            (7, 4),
+            (9, 4),
+            (7, 5),
+            (9, 5),
+        ];
+        let closing = [(12, 12), (7, 3)];
+
+        let freestyle = play(MnkGame::default(), &stones).unwrap();
+        let freestyle = play(freestyle, &[(7, 0), (12, 12), (7, 3)]).unwrap();
+        assert_eq!(freestyle.winner(), Some(Player::X));
+
+        // the same six in a row doesn't win under the exact rule, but five still does
+        let exact = MnkGame::default().with_exact_k(true);
+        let six = play(play(exact.clone(), &stones).unwrap(), &[(7, 0)]).unwrap();
+        let six = play(six, &closing).unwrap();
+        assert_eq!(six.winner(), None);
+        let five = play(play(exact, &stones).unwrap(), &[(12, 0)]).unwrap();
+        let five = play(five, &closing).unwrap();
+        assert_eq!(five.winner(), Some(Player::X));
+    }
+
+    #[test]
+    fn swap2_openings() {
+        let game = MnkGame::default().with_opening(Opening::Swap2);
+        assert!(game.in_opening());
+        let game = play(game, &[(7, 7), (7, 8), (8, 8)]).unwrap();
+        assert_eq!(game.current_player(), Player::O);
+        assert_eq!(game.legal_actions().len(), 3);
+        assert!(matches!(
+            game.clone().update(MnkAction::Place(0)),
+            Err(Error::InvalidChoice)
+        ));
+
+        // player O takes the X stones, so player X places the fourth stone, an O
+        let swapped = game.clone().update(MnkAction::Choose(Player::X)).unwrap();
+        assert!(!swapped.in_opening());
+        assert_eq!(swapped.next_stone(), Player::O);
+        assert_eq!(swapped.current_player(), Player::X);
+        assert_eq!(swapped.player_of(Player::X), Player::O);
+
+        // player O places two more and player X keeps the O stones
+        let game = game.update(MnkAction::PlaceTwo).unwrap();
+        assert_eq!(game.current_player(), Player::O);
+        let game = play(game, &[(6, 6), (9, 9)]).unwrap();
+        assert_eq!(game.current_player(), Player::X);
+        assert_eq!(game.legal_actions().len(), 2);
+        let game = game.update(MnkAction::Choose(Player::O)).unwrap();
+        assert_eq!(game.get(9, 9), Some(Player::X));
+        assert_eq!(game.current_player(), Player::X);
+        assert_eq!(game.player_of(Player::O), Player::X);
+
+        // player O now holds the X stones and wins with them
+        let game = play(game, &[(0, 0), (10, 10), (0, 1), (11, 11)]).unwrap();
+        assert_eq!(game.winner(), Some(Player::O));
+        assert_eq!(game.reward(), HashMap::from([(Player::O, 1.0)]));
+    }
+
+    #[test]
+    fn prints_the_tic_tac_toe_grid() {
+        let game = play(MnkGame::new(3, 3, 3).unwrap(), &[(1, 1), (0, 0)]).unwrap();
+        let mut expected = Game::default();
+        expected = GameState::step(expected, &Player::X, &4).unwrap();
+        expected = GameState::step(expected, &Player::O, &0).unwrap();
+        assert_eq!(
+            game.to_string()
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>(),
+            expected
+                .to_string()
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn searches_large_boards() {
+        // X has four in a row with both ends open on a 9x9 board
+        let game = play(
+            MnkGame::new(9, 9, 5).unwrap(),
+            &[
+                (4, 2),
+                (0, 0),
+                (4, 3),
+                (0, 8),
+                (4, 4),
+                (8, 0),
+                (4, 5),
+                (8, 8),
+            ],
+        )
+        .unwrap();
+        let mut tree = MonteCarloTree::seed_from_state(0, game.clone());
+        tree.search(&Ucb1::default(), &SearchBudget::new().iterations(2000));
+        let best = tree.best_action(MoveSelection::RobustChild).unwrap();
+        assert!(
+            [MnkAction::Place(4 * 9 + 1), MnkAction::Place(4 * 9 + 6)].contains(&best.action),
+            "{:?}\n{}",
+            best.action,
+            game
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{
+    error::{Error, Result},
+    Game, Outcome, Player, Rules,
+};
+use common::collections::{GameResult, GameState};
+use std::collections::HashMap;
+
+/// Notakto on several boards at once. Both players place Xs on any board that is still alive,
+/// and a board dies as soon as it has three in a row. Whoever kills the last board loses.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Notakto {
+    boards: Vec<Game>,
+    current_player: Player,
+}
+
+impl Notakto {
+    /// A game on `boards` empty boards. There has to be at least one.
+    pub fn new(boards: usize) -> Result<Self> {
+        if boards == 0 {
+            return Err(Error::NoBoards);
+        }
+        Ok(Self {
+            boards: vec![Game::default().with_rules(Rules::Notakto); boards],
+            current_player: Player::X,
+        })
+    }
+
+    pub fn boards(&self) -> &[Game] {
+        &self.boards
+    }
+
+    /// Whether the board at `idx` has no three in a row yet.
+    pub fn is_alive(&self, idx: usize) -> bool {
+        self.boards
+            .get(idx)
+            .is_some_and(|board| board.outcome() == Outcome::InProgress)
+    }
+
+    /// The player who didn't kill the last board, once every board is dead.
+    pub fn winner(&self) -> Option<Player> {
+        if (0..self.boards.len()).any(|idx| self.is_alive(idx)) {
+            None
+        } else {
+            Some(self.current_player)
+        }
+    }
+
+    /// Every board and cell the player to move may play.
+    pub fn legal_actions(&self) -> Vec<(usize, usize)> {
+        (0..self.boards.len())
+            .filter(|&idx| self.is_alive(idx))
+            .flat_map(|idx| {
+                self.boards[idx]
+                    .open_indices()
+                    .into_iter()
+                    .map(move |cell| (idx, cell))
+            })
+            .collect()
+    }
+
+    /// Places an X on `cell` of board `idx` for the player to move.
+    pub fn update(self, (idx, cell): (usize, usize)) -> Result<Self> {
+        if self.winner().is_some() {
+            return Err(Error::GameOver);
+        }
+        let player = self.current_player;
+        let open = self.is_alive(idx) && self.boards[idx].board().get(cell) == Some(&None);
+        if !open {
+            return Err(Error::InvalidBoardAction(player, idx, cell));
+        }
+
+        let mut boards = self.boards;
+        boards[idx] = GameState::step(boards[idx], &boards[idx].current_player(), &cell)?;
+        Ok(Self {
+            boards,
+            current_player: player.opponent(),
+        })
+    }
+}
+
+impl Default for Notakto {
+    /// Three boards, a common way to play.
+    fn default() -> Self {
+        Self::new(3).expect("three boards to be enough")
+    }
+}
+
+impl GameState for Notakto {
+    type Reward = f64;
+    type Action = (usize, usize);
+    type Player = Player;
+    type ActionIter = std::vec::IntoIter<(Self::Player, Self::Action)>;
+    type Error = Error;
+
+    fn current_player(&self) -> Self::Player {
+        self.current_player
+    }
+
+    /// One point to the winner. There are no draws.
+    fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
+        match self.winner() {
+            Some(player) => HashMap::from([(player, 1.0)]),
+            None => HashMap::default(),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.winner().is_some()
+    }
+
+    fn result(&self) -> Option<GameResult<Self::Player>> {
+        self.winner().map(GameResult::Win)
+    }
+
+    fn action_iter(&self) -> Self::ActionIter {
+        self.legal_actions()
+            .into_iter()
+            .map(|action| (self.current_player, action))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn step(self, player: &Self::Player, action: &Self::Action) -> Result<Self> {
+        if *player != self.current_player {
+            return Err(Error::WrongPlayer(*player));
+        }
+        self.update(*action)
+    }
+}
+
+/// The boards side by side, each drawn like a `Game` and separated by `#`.
+impl std::fmt::Display for Notakto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..3 {
+            if row > 0 {
+                let separator = vec!["---+---+---"; self.boards.len()].join("#");
+                writeln!(f, "{}", separator)?;
+            }
+            let line = self
+                .boards
+                .iter()
+                .map(|board| {
+                    (0..3)
+                        .map(|col| {
+                            format!(
+                                " {:1} ",
+                                board.board()[row * 3 + col].map_or(' ', char::from)
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("|")
+                })
+                .collect::<Vec<_>>()
+                .join("#");
+            writeln!(f, "{}", line.strip_suffix(' ').unwrap_or(&line))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Solver;
+    use common::collections::{MonteCarloTree, MoveSelection, SearchBudget, Ucb1};
+
+    fn play(game: Notakto, moves: &[(usize, usize)]) -> Result<Notakto> {
+        moves
+            .iter()
+            .try_fold(game, |game, &action| game.update(action))
+    }
+
+    #[test]
+    fn last_line_loses() {
+        // X kills board 0, O kills board 1 with the last line
+        let game = play(
+            Notakto::new(2).unwrap(),
+            &[(0, 0), (0, 1), (1, 0), (1, 4), (0, 2)],
+        )
+        .unwrap();
+        assert!(!game.is_alive(0));
+        assert!(game.is_alive(1));
+        assert!(game.legal_actions().iter().all(|&(idx, _)| idx == 1));
+        assert!(matches!(
+            game.clone().update((0, 4)),
+            Err(Error::InvalidBoardAction(Player::O, 0, 4))
+        ));
+        assert!(matches!(
+            game.clone().update((1, 9)),
+            Err(Error::InvalidBoardAction(Player::O, 1, 9))
+        ));
+        assert!(matches!(
+            game.clone().update((1, 0)),
+            Err(Error::InvalidBoardAction(Player::O, 1, 0))
+        ));
+        assert!(matches!(
+            game.clone().update((2, 0)),
+            Err(Error::InvalidBoardAction(Player::O, 2, 0))
+        ));
+        assert_eq!(game.winner(), None);
+
+        let game = game.update((1, 8)).unwrap();
+        assert_eq!(game.winner(), Some(Player::X));
+        assert_eq!(game.reward(), HashMap::from([(Player::X, 1.0)]));
+        assert!(game.boards()[1]
+            .board()
+            .iter()
+            .flatten()
+            .all(|&p| p == Player::X));
+        assert!(matches!(game.update((1, 1)), Err(Error::GameOver)));
+    }
+
+    #[test]
+    fn needs_a_board() {
+        assert!(matches!(Notakto::new(0), Err(Error::NoBoards)));
+        assert_eq!(Notakto::default().boards().len(), 3);
+    }
+
+    #[test]
+    fn single_board_matches_game() {
+        // a Notakto game on one board is a `Game` with Notakto rules
+        let moves = [0, 1, 5, 6];
+        let game = play(Notakto::new(1).unwrap(), &moves.map(|cell| (0, cell))).unwrap();
+        let single = moves
+            .iter()
+            .fold(Game::default().with_rules(Rules::Notakto), |game, cell| {
+                GameState::step(game, &game.current_player(), cell).unwrap()
+            });
+        assert_eq!(game.boards()[0], single);
+        assert_eq!(game.current_player(), single.current_player());
+
+        // the fifth X, at 2, completes the top row and loses
+        let game = game.update((0, 2)).unwrap();
+        let single = GameState::step(single, &Player::X, &2).unwrap();
+        assert_eq!(single.outcome(), Outcome::Win(Player::O));
+        assert_eq!(game.winner(), Some(Player::O));
+    }
+
+    #[test]
+    fn prints_boards_side_by_side() {
+        let game = play(Notakto::new(2).unwrap(), &[(0, 4), (1, 0)]).unwrap();
+        assert_eq!(
+            game.to_string(),
+            [
+                "   |   |   # X |   |  ",
+                "---+---+---#---+---+---",
+                "   | X |   #   |   |  ",
+                "---+---+---#---+---+---",
+                "   |   |   #   |   |  ",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn searches_with_mcts() {
+        // the opening is the hard one: only the center wins, and shallow searches miss it
+        let solver = Solver::with_rules(Rules::Notakto);
+        for moves in [&[][..], &[4], &[0, 1, 3], &[4, 0, 5]] {
+            let moves = moves.iter().map(|&cell| (0, cell)).collect::<Vec<_>>();
+            let game = play(Notakto::new(1).unwrap(), &moves).unwrap();
+            let mut tree = MonteCarloTree::seed_from_state(0, game.clone());
+            tree.search(&Ucb1::default(), &SearchBudget::new().iterations(50_000));
+            let (_, cell) = tree.best_action(MoveSelection::RobustChild).unwrap().action;
+            let optimal = solver.optimal_moves(&game.boards()[0]).unwrap();
+            assert!(optimal.contains(&cell), "{} after {:?}", cell, moves);
+        }
+    }
+}
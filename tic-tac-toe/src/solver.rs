@@ -0,0 +1,274 @@
+use crate::{Game, Outcome, Player, Rules};
+use common::collections::GameState;
+use nanorand::{Rng, WyRand};
+use std::collections::HashMap;
+
+/// The exact value of a position and every move that keeps it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    /// From the point of view of the player to move: 1 if they win with perfect play, 0 for a
+    /// draw and -1 if they lose.
+    pub value: i32,
+    /// Every move that achieves `value`, in index order. Empty once the game is over.
+    pub moves: Vec<usize>,
+}
+
+/// Every position reachable from the empty board, solved by exhaustive minimax. There are only
+/// 5,478 of them under the standard rules, so the whole game graph is built up front.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    rules: Rules,
+    /// keyed by the board and the player to move, so a game with the wrong player to move for
+    /// its board isn't mistaken for the real position.
+    table: HashMap<([Option<Player>; 9], Player), Solution>,
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self::with_rules(Rules::Standard)
+    }
+
+    /// Solves the game under `rules`. Only games played by the same rules can be looked up.
+    pub fn with_rules(rules: Rules) -> Self {
+        let mut out = Self {
+            rules,
+            table: HashMap::new(),
+        };
+        out.solve(Game::default().with_rules(rules));
+        out
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// The number of positions solved.
+    pub fn positions(&self) -> usize {
+        self.table.len()
+    }
+
+    /// The solution for `game`, or `None` if it can't be reached from the empty board, has the
+    /// wrong player to move, or is played by other rules.
+    pub fn solution(&self, game: &Game) -> Option<&Solution> {
+        if game.rules != self.rules {
+            return None;
+        }
+        self.table.get(&(game.board, game.current_player()))
+    }
+
+    /// The exact value of `game` for the player to move.
+    pub fn value(&self, game: &Game) -> Option<i32> {
+        self.solution(game).map(|solution| solution.value)
+    }
+
+    /// Every move that keeps the best result for the player to move.
+    pub fn optimal_moves(&self, game: &Game) -> Option<&[usize]> {
+        self.solution(game)
+            .map(|solution| solution.moves.as_slice())
+    }
+
+    /// negamax over every move, filling in the table on the way back up.
+    fn solve(&mut self, game: Game) -> i32 {
+        let key = (game.board, game.current_player());
+        if let Some(solution) = self.table.get(&key) {
+            return solution.value;
+        }
+
+        let solution = match game.outcome() {
+            Outcome::Win(player) => Solution {
+                value: if player == game.current_player() {
+                    1
+                } else {
+                    -1
+                },
+                moves: Vec::new(),
+            },
+            Outcome::Draw => Solution {
+                value: 0,
+                moves: Vec::new(),
+            },
+            Outcome::InProgress => {
+                let player = game.current_player();
+                let scores = game
+                    .open_indices()
+                    .into_iter()
+                    .map(|idx| {
+                        let child = GameState::step(game, &player, &idx)
+                            .expect("open indices to be playable");
+                        (idx, -self.solve(child))
+                    })
+                    .collect::<Vec<_>>();
+                let value = scores.iter().map(|&(_, score)| score).max().unwrap();
+                Solution {
+                    value,
+                    moves: scores
+                        .into_iter()
+                        .filter(|&(_, score)| score == value)
+                        .map(|(idx, _)| idx)
+                        .collect(),
+                }
+            }
+        };
+
+        let value = solution.value;
+        self.table.insert(key, solution);
+        value
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Plays a random optimal move in every position, so it never loses.
+#[derive(Debug, Clone)]
+pub struct PerfectAgent {
+    solver: Solver,
+    rng: WyRand,
+}
+
+impl PerfectAgent {
+    pub fn new() -> Self {
+        let seed = nanorand::tls_rng().generate();
+        Self::seed_from_u64(seed)
+    }
+
+    pub fn seed_from_u64(seed: u64) -> Self {
+        Self {
+            solver: Solver::new(),
+            rng: WyRand::new_seed(seed),
+        }
+    }
+
+    pub fn solver(&self) -> &Solver {
+        &self.solver
+    }
+
+    /// A move for the player to move, or `None` if the game is over or can't be reached.
+    pub fn step(&mut self, game: &Game) -> Option<usize> {
+        let moves = self.solver.optimal_moves(game)?;
+        if moves.is_empty() {
+            None
+        } else {
+            Some(moves[self.rng.generate_range(0..moves.len())])
+        }
+    }
+}
+
+impl Default for PerfectAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::collections::{MonteCarloTree, MoveSelection, SearchBudget, Ucb1};
+
+    fn play(moves: &[usize]) -> Game {
+        moves.iter().fold(Game::default(), |game, idx| {
+            let player = game.current_player();
+            GameState::step(game, &player, idx).unwrap()
+        })
+    }
+
+    #[test]
+    fn solves_every_position() {
+        let solver = Solver::new();
+        assert_eq!(solver.positions(), 5478);
+
+        let empty = solver.solution(&Game::default()).unwrap();
+        assert_eq!(empty.value, 0);
+        assert_eq!(empty.moves, (0..9).collect::<Vec<_>>());
+
+        // X takes the top row; O has to block it
+        assert_eq!(solver.value(&play(&[0, 4, 1])), Some(0));
+        assert_eq!(solver.optimal_moves(&play(&[0, 4, 1])), Some(&[2][..]));
+        // an edge reply to a corner opening loses for O
+        assert_eq!(solver.value(&play(&[0, 1])), Some(1));
+        // X has won
+        assert_eq!(solver.value(&play(&[0, 3, 1, 4, 2])), Some(-1));
+        // two X in a row without O ever moving
+        let mut board = [None; 9];
+        board[0] = Some(Player::X);
+        board[1] = Some(Player::X);
+        assert_eq!(solver.value(&Game::new(board, Player::X)), None);
+        // a reachable board, but with X to move again
+        let game = play(&[0, 4, 1]);
+        assert_eq!(solver.value(&Game::new(*game.board(), Player::X)), None);
+        assert_eq!(solver.value(&Game::new(*game.board(), Player::O)), Some(0));
+    }
+
+    #[test]
+    fn solves_other_rules() {
+        // the first player wins single-board Notakto by taking the center
+        let solver = Solver::with_rules(Rules::Notakto);
+        let empty = Game::default().with_rules(Rules::Notakto);
+        assert_eq!(solver.value(&empty), Some(1));
+        assert_eq!(solver.optimal_moves(&empty), Some(&[4][..]));
+        assert_eq!(solver.value(&Game::default()), None);
+
+        // misère tic-tac-toe is a draw, and X only keeps the draw by opening in the center
+        let solver = Solver::with_rules(Rules::Misere);
+        let empty = Game::default().with_rules(Rules::Misere);
+        assert_eq!(solver.value(&empty), Some(0));
+        assert_eq!(solver.optimal_moves(&empty), Some(&[4][..]));
+
+        // O completing the top row loses
+        let game = play(&[4, 0, 8, 1, 6]).with_rules(Rules::Misere);
+        assert!(!solver.optimal_moves(&game).unwrap().contains(&2));
+    }
+
+    #[test]
+    fn perfect_play_draws() {
+        let mut agent = PerfectAgent::seed_from_u64(0);
+        for _ in 0..20 {
+            let mut game = Game::default();
+            while let Some(idx) = agent.step(&game) {
+                let player = game.current_player();
+                game = GameState::step(game, &player, &idx).unwrap();
+            }
+            assert_eq!(game.outcome(), Outcome::Draw);
+        }
+    }
+
+    #[test]
+    fn never_loses_to_random_play() {
+        let mut agent = PerfectAgent::seed_from_u64(1);
+        let mut rng = WyRand::new_seed(2);
+        for perfect in [Player::X, Player::O].into_iter().cycle().take(100) {
+            let mut game = Game::default();
+            while !game.is_complete() {
+                let player = game.current_player();
+                let idx = if player == perfect {
+                    agent.step(&game).unwrap()
+                } else {
+                    let open = game.open_indices();
+                    open[rng.generate_range(0..open.len())]
+                };
+                game = GameState::step(game, &player, &idx).unwrap();
+            }
+            assert_ne!(game.outcome(), Outcome::Win(perfect.opponent()));
+        }
+    }
+
+    #[test]
+    fn mcts_finds_optimal_moves() {
+        let solver = Solver::new();
+        for moves in [&[][..], &[0, 1], &[4, 0, 8], &[0, 4, 1], &[4, 1]] {
+            let game = play(moves);
+            let mut tree = MonteCarloTree::seed_from_state(0, game);
+            tree.search(&Ucb1::default(), &SearchBudget::new().iterations(20000));
+            let best = tree.best_action(MoveSelection::RobustChild).unwrap();
+            assert!(
+                solver.optimal_moves(&game).unwrap().contains(&best.action),
+                "{:?} after {:?}",
+                best.action,
+                moves
+            );
+        }
+    }
+}
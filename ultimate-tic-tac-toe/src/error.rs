@@ -0,0 +1,34 @@
+use tic_tac_toe::Player;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// a cell that is taken, on a board that is finished, or off the board altogether.
+    InvalidAction(Player, usize, usize),
+    /// the board the player tried, and the board the previous move sent them to.
+    WrongBoard(usize, usize),
+    WrongPlayer(Player),
+    GameOver,
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidAction(player, board, cell) => write!(
+                f,
+                "{:?} cannot play at index {} of board {}",
+                player, cell, board
+            ),
+            Self::WrongBoard(board, sent) => write!(
+                f,
+                "cannot play on board {} after being sent to board {}",
+                board, sent
+            ),
+            Self::WrongPlayer(player) => write!(f, "it is not {:?}'s turn", player),
+            Self::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use common::collections::{GameResult, GameState};
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
+use tic_tac_toe::{Outcome, Player};
+
+pub mod error;
+
+use error::{Error, Result};
+
+/// Ultimate tic-tac-toe: nine games of tic-tac-toe laid out in a three by three grid. Winning a
+/// small board claims that square of the big board, and three claimed squares in a row win the
+/// game.
+///
+/// The cell a player picks on a small board sends their opponent to the small board in the same
+/// position on the big one. If that board is already won or full, the opponent may play on any
+/// board that is still open. A game where every board is finished without three in a row is a
+/// draw.
+///
+/// Games compare and hash by position: the boards and whose turn it is where. The last move is
+/// left out, so transpositions compare equal.
+#[derive(Debug, Clone)]
+pub struct Game {
+    boards: [tic_tac_toe::Game; 9],
+    /// the result of each small board, updated as moves are made rather than read off the
+    /// boards every time.
+    outcomes: [Outcome; 9],
+    /// the result of the whole game, updated along with `outcomes`.
+    outcome: Outcome,
+    current_player: Player,
+    /// the board the player to move has to play on, or `None` if they may pick any open board.
+    next_board: Option<usize>,
+    previous_move: Option<(usize, usize)>,
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Self {
+            boards: Default::default(),
+            outcomes: [Outcome::InProgress; 9],
+            outcome: Outcome::InProgress,
+            current_player: Player::default(),
+            next_board: None,
+            previous_move: None,
+        }
+    }
+
+    /// The small board at `idx`, counting row by row from the top left.
+    pub fn board(&self, idx: usize) -> &tic_tac_toe::Game {
+        &self.boards[idx]
+    }
+
+    /// The board the player to move has been sent to, or `None` if they may play on any open
+    /// board.
+    pub fn next_board(&self) -> Option<usize> {
+        self.next_board
+    }
+
+    /// The board and cell of the last move.
+    pub fn previous_move(&self) -> Option<(usize, usize)> {
+        self.previous_move
+    }
+
+    /// The big board: each square holds whoever won the small board there.
+    pub fn claimed(&self) -> [Option<Player>; 9] {
+        claimed(&self.outcomes)
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        self.outcome
+    }
+
+    /// Every board and cell the player to move may play.
+    pub fn legal_actions(&self) -> Vec<(usize, usize)> {
+        if self.outcome() != Outcome::InProgress {
+            return Vec::new();
+        }
+        let boards = match self.next_board {
+            Some(board) => vec![board],
+            None => self.open_boards().collect(),
+        };
+        boards
+            .into_iter()
+            .flat_map(|board| {
+                self.boards[board]
+                    .open_indices()
+                    .into_iter()
+                    .map(move |cell| (board, cell))
+            })
+            .collect()
+    }
+
+    /// Plays `cell` on `board` for the player to move.
+    pub fn update(self, (board, cell): (usize, usize)) -> Result<Self> {
+        if self.outcome() != Outcome::InProgress {
+            return Err(Error::GameOver);
+        }
+        let player = self.current_player;
+        if let Some(sent) = self.next_board.filter(|&sent| sent != board) {
+            return Err(Error::WrongBoard(board, sent));
+        }
+        let open = self.outcomes.get(board) == Some(&Outcome::InProgress)
+            && self.boards[board].board().get(cell) == Some(&None);
+        if !open {
+            return Err(Error::InvalidAction(player, board, cell));
+        }
+
+        let mut boards = self.boards;
+        let mut cells = *boards[board].board();
+        cells[cell] = Some(player);
+        boards[board] = tic_tac_toe::Game::new(cells, player.opponent());
+
+        // only the board just played on can have changed
+        let mut outcomes = self.outcomes;
+        outcomes[board] = boards[board].outcome();
+        let outcome = match outcomes[board] {
+            Outcome::InProgress => Outcome::InProgress,
+            _ => overall(&outcomes),
+        };
+
+        let next_board = Some(cell).filter(|&next| outcomes[next] == Outcome::InProgress);
+        Ok(Self {
+            boards,
+            outcomes,
+            outcome,
+            current_player: player.opponent(),
+            next_board,
+            previous_move: Some((board, cell)),
+        })
+    }
+
+    /// boards that are neither won nor full.
+    fn open_boards(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..9).filter(|&idx| self.outcomes[idx] == Outcome::InProgress)
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for Game {
+    fn eq(&self, other: &Self) -> bool {
+        // the outcomes follow from the boards
+        self.boards == other.boards
+            && self.current_player == other.current_player
+            && self.next_board == other.next_board
+    }
+}
+
+impl Eq for Game {}
+
+impl Hash for Game {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.boards.hash(state);
+        self.current_player.hash(state);
+        self.next_board.hash(state);
+    }
+}
+
+/// whoever won each small board.
+fn claimed(outcomes: &[Outcome; 9]) -> [Option<Player>; 9] {
+    outcomes.map(|outcome| match outcome {
+        Outcome::Win(player) => Some(player),
+        Outcome::Draw | Outcome::InProgress => None,
+    })
+}
+
+/// the result of the whole game given the result of each small board.
+fn overall(outcomes: &[Outcome; 9]) -> Outcome {
+    // three claimed squares in a row are checked like a game of tic-tac-toe
+    let big = tic_tac_toe::Game::new(claimed(outcomes), Player::X);
+    match big.outcome() {
+        Outcome::Win(player) => Outcome::Win(player),
+        _ if outcomes.contains(&Outcome::InProgress) => Outcome::InProgress,
+        _ => Outcome::Draw,
+    }
+}
+
+impl GameState for Game {
+    type Reward = f64;
+    type Action = (usize, usize);
+    type Player = Player;
+    type ActionIter = std::vec::IntoIter<(Self::Player, Self::Action)>;
+    type Error = Error;
+
+    fn current_player(&self) -> Self::Player {
+        self.current_player
+    }
+
+    /// One point to the winner, or half a point each for a draw.
+    fn reward(&self) -> HashMap<Self::Player, Self::Reward> {
+        match self.outcome() {
+            Outcome::Win(player) => HashMap::from([(player, 1.0)]),
+            Outcome::Draw => HashMap::from([(Player::X, 0.5), (Player::O, 0.5)]),
+            Outcome::InProgress => HashMap::default(),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.outcome() != Outcome::InProgress
+    }
+
+    fn result(&self) -> Option<GameResult<Self::Player>> {
+        match self.outcome() {
+            Outcome::Win(player) => Some(GameResult::Win(player)),
+            Outcome::Draw => Some(GameResult::Draw),
+            Outcome::InProgress => None,
+        }
+    }
+
+    fn action_iter(&self) -> Self::ActionIter {
+        self.legal_actions()
+            .into_iter()
+            .map(|action| (self.current_player, action))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn step(self, player: &Self::Player, action: &Self::Action) -> Result<Self> {
+        if *player != self.current_player {
+            return Err(Error::WrongPlayer(*player));
+        }
+        self.update(*action)
+    }
+}
+
+/// The small boards are drawn like `tic_tac_toe::Game`, separated by `#` and `=`.
+impl std::fmt::Display for Game {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..9 {
+            if row > 0 && row % 3 == 0 {
+                writeln!(f, "===========#===========#===========")?;
+            } else if row > 0 {
+                writeln!(f, "---+---+---#---+---+---#---+---+---")?;
+            }
+            let line = (0..3)
+                .map(|big_col| {
+                    let board = &self.boards[row / 3 * 3 + big_col];
+                    (0..3)
+                        .map(|col| {
+                            let cell = board.board()[row % 3 * 3 + col];
+                            format!(" {:1} ", cell.map_or(' ', char::from))
+                        })
+                        .collect::<Vec<_>>()
+                        .join("|")
+                })
+                .collect::<Vec<_>>()
+                .join("#");
+            // like `tic_tac_toe::Game`, rows end right after the last cell
+            writeln!(f, "{}", line.strip_suffix(' ').unwrap_or(&line))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::collections::{MonteCarloTree, MoveSelection, SearchBudget, Ucb1};
+    use nanorand::{Rng, WyRand};
+
+    fn play(moves: &[(usize, usize)]) -> Result<Game> {
+        moves
+            .iter()
+            .try_fold(Game::new(), |game, &action| game.update(action))
+    }
+
+    #[test]
+    fn sends_the_opponent_to_a_board() {
+        let game = Game::new();
+        assert_eq!(game.legal_actions().len(), 81);
+
+        let game = game.update((4, 2)).unwrap();
+        assert_eq!(game.next_board(), Some(2));
+        assert_eq!(game.current_player(), Player::O);
+        assert!(game.legal_actions().iter().all(|&(board, _)| board == 2));
+        assert_eq!(game.legal_actions().len(), 9);
+        assert!(matches!(
+            game.clone().update((4, 0)),
+            Err(Error::WrongBoard(4, 2))
+        ));
+        assert!(matches!(
+            game.update((2, 2)).unwrap().update((2, 2)),
+            Err(Error::InvalidAction(Player::X, 2, 2))
+        ));
+    }
+
+    #[test]
+    fn redirects_from_finished_boards() {
+        // X takes the top row of board 0 while O keeps sending X back there
+        let game = play(&[(0, 1), (1, 0), (0, 2), (2, 0)]).unwrap();
+        assert_eq!(game.next_board(), Some(0));
+        let game = game.update((0, 0)).unwrap();
+        assert_eq!(game.board(0).outcome(), Outcome::Win(Player::X));
+        assert_eq!(game.claimed()[0], Some(Player::X));
+
+        // the last move pointed at board 0, which is won, so O may play on any other board
+        assert_eq!(game.next_board(), None);
+        let actions = game.legal_actions();
+        assert!(actions.iter().all(|&(board, _)| board != 0));
+        assert_eq!(actions.len(), 81 - 9 - 2);
+        assert!(matches!(
+            game.update((0, 4)),
+            Err(Error::InvalidAction(Player::O, 0, 4))
+        ));
+    }
+
+    #[test]
+    fn wins_three_boards_in_a_row() {
+        // X takes the left column of boards 0 and 1 and the top row of board 2, and O sends X
+        // wherever it needs to go
+        let game = play(&[
+            (0, 0),
+            (0, 1),
+            (1, 0),
+            (0, 2),
+            (2, 1),
+            (1, 1),
+            (1, 3),
+            (3, 0),
+            (0, 3),
+            (3, 1),
+            (1, 6),
+            (6, 0),
+            (0, 6),
+            (6, 1),
+            (2, 0),
+            (4, 0),
+        ])
+        .unwrap();
+        assert_eq!(game.outcome(), Outcome::InProgress);
+        // O's last move points at board 0, which X has won, so X may finish board 2
+        assert_eq!(game.next_board(), None);
+
+        let game = game.update((2, 2)).unwrap();
+        let mut claimed = [None; 9];
+        claimed[..3].fill(Some(Player::X));
+        assert_eq!(game.claimed(), claimed);
+        assert_eq!(game.outcome(), Outcome::Win(Player::X), "\n{}", game);
+        assert_eq!(game.reward(), HashMap::from([(Player::X, 1.0)]));
+        assert!(game.legal_actions().is_empty());
+        assert!(matches!(game.update((5, 5)), Err(Error::GameOver)));
+    }
+
+    #[test]
+    fn random_games_end_in_wins_or_draws() {
+        let mut rng = WyRand::new_seed(0);
+        let mut draws = 0;
+        for _ in 0..500 {
+            let mut game = Game::new();
+            while !game.is_complete() {
+                let actions = game.legal_actions();
+                if let Some(board) = game.next_board() {
+                    assert!(actions.iter().all(|&(idx, _)| idx == board));
+                }
+                let (board, cell) = actions[rng.generate_range(0..actions.len())];
+                game = game.update((board, cell)).unwrap();
+
+                // the stored results always match the boards
+                for idx in 0..9 {
+                    assert_eq!(game.outcomes[idx], game.board(idx).outcome());
+                }
+                assert_eq!(game.outcome(), overall(&game.outcomes));
+            }
+            assert!(game.legal_actions().is_empty());
+            match game.outcome() {
+                Outcome::Win(player) => {
+                    assert_eq!(game.reward(), HashMap::from([(player, 1.0)]))
+                }
+                Outcome::Draw => {
+                    draws += 1;
+                    assert_eq!(
+                        game.reward(),
+                        HashMap::from([(Player::X, 0.5), (Player::O, 0.5)])
+                    );
+                }
+                Outcome::InProgress => unreachable!(),
+            }
+        }
+        assert!(draws > 0);
+    }
+
+    #[test]
+    fn prints_nine_boards() {
+        let game = play(&[(4, 4), (4, 0)]).unwrap();
+        let text = game.to_string();
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 17);
+        assert_eq!(lines[0], "   |   |   #   |   |   #   |   |  ");
+        assert_eq!(lines[1], "---+---+---#---+---+---#---+---+---");
+        assert_eq!(lines[5], "===========#===========#===========");
+        assert_eq!(lines[6], "   |   |   # O |   |   #   |   |  ");
+        assert_eq!(lines[8], "   |   |   #   | X |   #   |   |  ");
+    }
+
+    #[test]
+    fn ignores_the_last_move() {
+        let game = play(&[(4, 2), (2, 4)]).unwrap();
+        let other = Game {
+            previous_move: Some((0, 0)),
+            ..game.clone()
+        };
+        assert_eq!(game, other);
+        let hash = |game: &Game| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            game.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&game), hash(&other));
+        assert_ne!(game, play(&[(4, 2), (2, 5)]).unwrap());
+    }
+
+    #[test]
+    fn searches_with_mcts() {
+        let mut tree = MonteCarloTree::<Game>::seed_from_u64(0);
+        tree.search(&Ucb1::default(), &SearchBudget::new().iterations(500));
+        let best = tree.best_action(MoveSelection::RobustChild).unwrap();
+        assert!(Game::new().legal_actions().contains(&best.action));
+    }
+}
@@ -0,0 +1,21 @@
+use common::collections::{GameState, MonteCarloTree, MoveSelection, SearchBudget, Ucb1};
+use ultimate_tic_tac_toe::Game;
+
+fn main() {
+    let mut game = Game::new();
+    let mut tree = MonteCarloTree::<Game>::new();
+    let budget = SearchBudget::new().iterations(20_000);
+
+    while !game.is_complete() {
+        tree.search(&Ucb1::default(), &budget);
+        let best = tree.best_action(MoveSelection::RobustChild).unwrap();
+        let (board, cell) = best.action;
+        println!("{} plays cell {} of board {}", best.player, cell, board);
+
+        game = game.step(&best.player, &best.action).unwrap();
+        tree.advance(&best.action).unwrap();
+        println!("{}", game);
+    }
+
+    println!("{:?}", game.reward());
+}
@@ -0,0 +1,31 @@
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    /// a file that isn't a pattern matrix cache at all.
+    MalformedCache,
+    /// a pattern matrix cache that was cut short, or runs on past its codes.
+    TruncatedCache,
+    /// a cache file computed for other guess or solution lists.
+    StaleCache,
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::MalformedCache => write!(f, "the cache file is not a pattern matrix"),
+            Self::TruncatedCache => write!(f, "the cache file has the wrong length"),
+            Self::StaleCache => write!(f, "the cache file was computed for other words"),
+        }
+    }
+}
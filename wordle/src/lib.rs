@@ -1,6 +1,12 @@
+pub mod error;
+mod pattern;
+mod solver;
 mod words;
+pub use pattern::PatternMatrix;
+pub use solver::{Solver, Statistics};
 pub use words::{GUESSES, SOLUTIONS};
 
+#[deprecated(note = "this needs gigabytes of memory; use `PatternMatrix` instead")]
 pub fn calculate_word_pairs() -> Vec<(Word, Word, Response)> {
     let mut map = Vec::with_capacity(GUESSES.len() * SOLUTIONS.len());
     for guess in GUESSES.iter() {
@@ -15,10 +21,51 @@ pub fn calculate_word_pairs() -> Vec<(Word, Word, Response)> {
     map
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Response([Clue; 5]);
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+impl Response {
+    /// The number of distinct responses, and one more than the largest code.
+    pub const PATTERNS: usize = 243;
+
+    /// Whether every letter is in the right place.
+    pub fn is_solved(&self) -> bool {
+        self.0 == [Clue::Correct; 5]
+    }
+
+    /// The response as a base 3 number, one digit per letter with the first letter least
+    /// significant. Incorrect is 0, misplaced 1 and correct 2, so a solved response is 242.
+    pub fn code(&self) -> u8 {
+        self.0.iter().rev().fold(0, |code, clue| {
+            let digit = match clue {
+                Clue::Incorrect => 0,
+                Clue::Misplaced => 1,
+                Clue::Correct => 2,
+            };
+            code * 3 + digit
+        })
+    }
+
+    /// The response with the given code, or `None` if it is 243 or more.
+    pub fn from_code(code: u8) -> Option<Self> {
+        if usize::from(code) >= Self::PATTERNS {
+            return None;
+        }
+        let mut rest = code;
+        let mut clues = [Clue::Incorrect; 5];
+        for clue in clues.iter_mut() {
+            *clue = match rest % 3 {
+                0 => Clue::Incorrect,
+                1 => Clue::Misplaced,
+                _ => Clue::Correct,
+            };
+            rest /= 3;
+        }
+        Some(Self(clues))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum Clue {
     Correct,
     Incorrect,
@@ -82,4 +129,18 @@ mod tests {
             assert_eq!(evaluate_guess(guess, secret), Response(response));
         }
     }
+
+    #[test]
+    fn codes_round_trip() {
+        assert_eq!(Response([I; 5]).code(), 0);
+        assert_eq!(Response([M, I, I, I, I]).code(), 1);
+        assert_eq!(Response([I, I, I, I, C]).code(), 162);
+        assert_eq!(Response([C; 5]).code(), 242);
+        for code in 0..=255 {
+            match Response::from_code(code) {
+                Some(response) => assert_eq!(response.code(), code),
+                None => assert!(usize::from(code) >= Response::PATTERNS),
+            }
+        }
+    }
 }
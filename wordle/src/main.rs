@@ -1,3 +1,24 @@
-fn main() {
-    println!("Hello world!")
+use wordle::{PatternMatrix, Solver, GUESSES, SOLUTIONS};
+
+fn main() -> wordle::error::Result<()> {
+    // an optional path to cache the pattern matrix in between runs
+    let matrix = match std::env::args().nth(1) {
+        Some(path) => PatternMatrix::load_or_new(path, &GUESSES[..], &SOLUTIONS[..])?,
+        None => PatternMatrix::new(&GUESSES[..], &SOLUTIONS[..]),
+    };
+    let mut solver = Solver::with_matrix(matrix);
+    let opening = solver.best_guess().unwrap();
+    println!("opening with {}", opening);
+
+    let stats = solver.solve_all();
+    for (idx, games) in stats.histogram.iter().enumerate() {
+        println!("{} guesses: {} games", idx + 1, games);
+    }
+    println!(
+        "solved {} words in {:.4} guesses on average, {} at worst",
+        stats.games(),
+        stats.average(),
+        stats.worst()
+    );
+    Ok(())
 }
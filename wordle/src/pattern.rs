@@ -0,0 +1,275 @@
+use crate::{
+    error::{Error, Result},
+    evaluate_guess,
+};
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    path::Path,
+};
+
+/// The first bytes of every cache file, including a format version.
+const MAGIC: &[u8; 8] = b"WORDLE01";
+
+/// The response code of every guess against every solution, one byte each in a flat, guess-major
+/// table. The full word lists need about 30 MB.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternMatrix {
+    guesses: Vec<&'static str>,
+    solutions: Vec<&'static str>,
+    /// the code for guess `g` against solution `s` is at `g * solutions.len() + s`.
+    codes: Vec<u8>,
+    guess_index: HashMap<&'static str, usize>,
+    /// the position in the solution list of each guess, if it is also a solution.
+    solution_index: Vec<Option<usize>>,
+}
+
+impl PatternMatrix {
+    /// Computes the matrix on every available core.
+    pub fn new(guesses: &[&'static str], solutions: &[&'static str]) -> Self {
+        let threads = std::thread::available_parallelism().map_or(1, usize::from);
+        Self::with_threads(guesses, solutions, threads)
+    }
+
+    /// Computes the matrix on `threads` threads, each filling its own block of guesses.
+    pub fn with_threads(
+        guesses: &[&'static str],
+        solutions: &[&'static str],
+        threads: usize,
+    ) -> Self {
+        let mut codes = vec![0; guesses.len() * solutions.len()];
+        if !codes.is_empty() {
+            let rows = guesses.len().div_ceil(threads.max(1));
+            std::thread::scope(|scope| {
+                for (block, chunk) in codes.chunks_mut(rows * solutions.len()).enumerate() {
+                    let guesses = &guesses[block * rows..];
+                    scope.spawn(move || {
+                        for (row, &guess) in chunk.chunks_mut(solutions.len()).zip(guesses) {
+                            for (code, &solution) in row.iter_mut().zip(solutions) {
+                                *code = evaluate_guess(guess, solution).code();
+                            }
+                        }
+                    });
+                }
+            });
+        }
+        Self::from_codes(guesses, solutions, codes)
+    }
+
+    /// Reads a matrix saved by `save`, checking it was computed for these exact word lists.
+    pub fn load(
+        path: impl AsRef<Path>,
+        guesses: &[&'static str],
+        solutions: &[&'static str],
+    ) -> Result<Self> {
+        let mut bytes = Vec::new();
+        std::fs::File::open(path)?.read_to_end(&mut bytes)?;
+
+        if !bytes.starts_with(MAGIC) {
+            return Err(Error::MalformedCache);
+        }
+        let header = bytes.get(..16).ok_or(Error::TruncatedCache)?;
+        let count = |at: usize| {
+            let mut len = [0; 4];
+            len.copy_from_slice(&header[at..at + 4]);
+            u32::from_le_bytes(len) as usize
+        };
+        if (count(8), count(12)) != (guesses.len(), solutions.len()) {
+            return Err(Error::StaleCache);
+        }
+
+        let words = guesses
+            .iter()
+            .chain(solutions)
+            .flat_map(|word| word.bytes());
+        let (body, codes) = bytes[16..]
+            .split_at_checked(5 * (guesses.len() + solutions.len()))
+            .ok_or(Error::TruncatedCache)?;
+        if codes.len() != guesses.len() * solutions.len() {
+            return Err(Error::TruncatedCache);
+        }
+        if !body.iter().copied().eq(words) {
+            return Err(Error::StaleCache);
+        }
+        Ok(Self::from_codes(guesses, solutions, codes.to_vec()))
+    }
+
+    /// Writes the matrix to `path`, along with the word lists it was computed for. The file is
+    /// written next to `path` first and renamed over it, so it is never left half written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut partial = path.as_ref().as_os_str().to_owned();
+        partial.push(".partial");
+        self.write(&partial)?;
+        std::fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&(self.guesses.len() as u32).to_le_bytes())?;
+        file.write_all(&(self.solutions.len() as u32).to_le_bytes())?;
+        for word in self.guesses.iter().chain(self.solutions.iter()) {
+            file.write_all(word.as_bytes())?;
+        }
+        file.write_all(&self.codes)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Loads the matrix from `path` if it holds one for these word lists. If there is no file
+    /// there, or only a cache for other words or a cut short one, computes the matrix and saves
+    /// it there for next time. Any other file is left alone and reported as malformed.
+    pub fn load_or_new(
+        path: impl AsRef<Path>,
+        guesses: &[&'static str],
+        solutions: &[&'static str],
+    ) -> Result<Self> {
+        match Self::load(&path, guesses, solutions) {
+            Ok(matrix) => Ok(matrix),
+            Err(Error::StaleCache | Error::TruncatedCache) => {
+                Self::rebuild(path, guesses, solutions)
+            }
+            Err(Error::Io(error)) if error.kind() == std::io::ErrorKind::NotFound => {
+                Self::rebuild(path, guesses, solutions)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn guesses(&self) -> &[&'static str] {
+        &self.guesses
+    }
+
+    pub fn solutions(&self) -> &[&'static str] {
+        &self.solutions
+    }
+
+    /// The position of `guess` in the guess list.
+    pub fn guess_index(&self, guess: &str) -> Option<usize> {
+        self.guess_index.get(guess).copied()
+    }
+
+    /// The position in the solution list of the guess at `guess`, if it is also a solution.
+    pub fn solution_index(&self, guess: usize) -> Option<usize> {
+        self.solution_index[guess]
+    }
+
+    /// The response code for the guess and solution at these positions.
+    pub fn code(&self, guess: usize, solution: usize) -> u8 {
+        self.codes[guess * self.solutions.len() + solution]
+    }
+
+    /// The response codes for a guess against every solution, in order.
+    pub fn row(&self, guess: usize) -> &[u8] {
+        let len = self.solutions.len();
+        &self.codes[guess * len..(guess + 1) * len]
+    }
+
+    fn rebuild(
+        path: impl AsRef<Path>,
+        guesses: &[&'static str],
+        solutions: &[&'static str],
+    ) -> Result<Self> {
+        let matrix = Self::new(guesses, solutions);
+        matrix.save(path)?;
+        Ok(matrix)
+    }
+
+    fn from_codes(guesses: &[&'static str], solutions: &[&'static str], codes: Vec<u8>) -> Self {
+        // the first occurrence wins if a word is listed twice
+        let mut guess_index = HashMap::with_capacity(guesses.len());
+        for (idx, &guess) in guesses.iter().enumerate() {
+            guess_index.entry(guess).or_insert(idx);
+        }
+        let mut solutions_by_word = HashMap::with_capacity(solutions.len());
+        for (idx, &solution) in solutions.iter().enumerate() {
+            solutions_by_word.entry(solution).or_insert(idx);
+        }
+        let solution_index = guesses
+            .iter()
+            .map(|guess| solutions_by_word.get(guess).copied())
+            .collect();
+        Self {
+            guesses: guesses.to_vec(),
+            solutions: solutions.to_vec(),
+            codes,
+            guess_index,
+            solution_index,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SOLUTIONS;
+
+    #[test]
+    fn matches_evaluate_guess() {
+        let guesses = &SOLUTIONS[..150];
+        let solutions = &SOLUTIONS[100..200];
+        let matrix = PatternMatrix::with_threads(guesses, solutions, 7);
+        assert_eq!(matrix, PatternMatrix::with_threads(guesses, solutions, 1));
+        for (g, &guess) in guesses.iter().enumerate() {
+            assert_eq!(matrix.guess_index(guess), Some(g));
+            for (s, &solution) in solutions.iter().enumerate() {
+                assert_eq!(matrix.code(g, s), evaluate_guess(guess, solution).code());
+            }
+        }
+        // the shared words answer themselves
+        assert_eq!(matrix.row(100)[0], 242);
+        assert_eq!(matrix.solution_index(100), Some(0));
+        assert_eq!(matrix.solution_index(149), Some(49));
+        assert_eq!(matrix.solution_index(99), None);
+        assert_eq!(matrix.guess_index("zzzzz"), None);
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let path = std::env::temp_dir().join(format!("wordle-patterns-{}", std::process::id()));
+        let (guesses, solutions) = (&SOLUTIONS[..40], &SOLUTIONS[..30]);
+        let matrix = PatternMatrix::load_or_new(&path, guesses, solutions).unwrap();
+        assert_eq!(matrix, PatternMatrix::new(guesses, solutions));
+        assert_eq!(
+            PatternMatrix::load(&path, guesses, solutions).unwrap(),
+            matrix
+        );
+
+        // other words, in the same or a different number
+        assert!(matches!(
+            PatternMatrix::load(&path, &SOLUTIONS[1..41], solutions),
+            Err(Error::StaleCache)
+        ));
+        assert!(matches!(
+            PatternMatrix::load(&path, guesses, &SOLUTIONS[..31]),
+            Err(Error::StaleCache)
+        ));
+
+        // a truncated file
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(matches!(
+            PatternMatrix::load(&path, guesses, solutions),
+            Err(Error::TruncatedCache)
+        ));
+        // which gets replaced
+        let reloaded = PatternMatrix::load_or_new(&path, guesses, solutions).unwrap();
+        assert_eq!(reloaded, matrix);
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+
+        // any other file is never overwritten
+        std::fs::write(&path, "[package]\n").unwrap();
+        assert!(matches!(
+            PatternMatrix::load_or_new(&path, guesses, solutions),
+            Err(Error::MalformedCache)
+        ));
+        assert_eq!(std::fs::read(&path).unwrap(), b"[package]\n");
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            PatternMatrix::load(&path, guesses, solutions),
+            Err(Error::Io(_))
+        ));
+    }
+}
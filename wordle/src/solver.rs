@@ -0,0 +1,298 @@
+use crate::{evaluate_guess, PatternMatrix, Response, GUESSES, SOLUTIONS};
+use std::collections::HashMap;
+
+/// Plays Wordle by always guessing the word that tells it the most about the secret: the one
+/// whose responses, over the words still possible, have the highest entropy.
+///
+/// Everything is deterministic, so the same secret is always solved with the same guesses. Ties
+/// go to words that could still be the answer, then to whichever comes first in the guess list.
+/// When no allowed guess can tell the candidates apart, it guesses them one at a time instead.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    matrix: PatternMatrix,
+    /// positions in the solution list of the solutions consistent with every response so far.
+    candidates: Vec<usize>,
+    /// the best guess for every candidate set seen so far. games share their openings, so most
+    /// positions come up again and again when solving every secret.
+    cache: HashMap<Vec<usize>, &'static str>,
+}
+
+/// How a solver did over a set of secrets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statistics {
+    /// The number of games won in exactly `i + 1` guesses at index `i`.
+    pub histogram: Vec<usize>,
+    /// The number of secrets the solver couldn't find, which are left out of `histogram`.
+    pub failed: usize,
+}
+
+impl Solver {
+    /// A solver that may guess any allowed word and knows the secret is one of `SOLUTIONS`.
+    pub fn new() -> Self {
+        Self::with_words(&GUESSES[..], &SOLUTIONS[..])
+    }
+
+    /// A solver that only guesses from `guesses` and looks for the secret among `solutions`.
+    pub fn with_words(guesses: &[&'static str], solutions: &[&'static str]) -> Self {
+        Self::with_matrix(PatternMatrix::new(guesses, solutions))
+    }
+
+    /// A solver over the words of an already computed, or loaded, matrix.
+    pub fn with_matrix(matrix: PatternMatrix) -> Self {
+        Self {
+            candidates: (0..matrix.solutions().len()).collect(),
+            matrix,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn matrix(&self) -> &PatternMatrix {
+        &self.matrix
+    }
+
+    /// The solutions that fit every response so far.
+    pub fn candidates(&self) -> impl ExactSizeIterator<Item = &'static str> + '_ {
+        let solutions = self.matrix.solutions();
+        self.candidates.iter().map(|&idx| solutions[idx])
+    }
+
+    /// Starts a new game, keeping what was learned about the best guesses.
+    pub fn reset(&mut self) {
+        self.candidates = (0..self.matrix.solutions().len()).collect();
+    }
+
+    /// The entropy, in bits, of the responses `guess` would get from the remaining candidates.
+    pub fn entropy(&self, guess: &str) -> f64 {
+        match self.matrix.guess_index(guess) {
+            Some(idx) => self.entropy_of(idx),
+            None => self.split_entropy(self.codes(guess)),
+        }
+    }
+
+    /// The guess with the most expected information, or `None` once no candidate is left.
+    pub fn best_guess(&mut self) -> Option<&'static str> {
+        let solutions = self.matrix.solutions();
+        match self.candidates.as_slice() {
+            [] => return None,
+            [only] => return Some(solutions[*only]),
+            _ => {}
+        }
+        if let Some(&guess) = self.cache.get(&self.candidates) {
+            return Some(guess);
+        }
+
+        let mut best = None;
+        let mut best_score = (f64::NEG_INFINITY, false);
+        let mut is_candidate = vec![false; solutions.len()];
+        for &idx in self.candidates.iter() {
+            is_candidate[idx] = true;
+        }
+        for idx in 0..self.matrix.guesses().len() {
+            let candidate = self
+                .matrix
+                .solution_index(idx)
+                .is_some_and(|solution| is_candidate[solution]);
+            let score = (self.entropy_of(idx), candidate);
+            // entropies within rounding error of each other count as a tie
+            let better = score.0 > best_score.0 + 1e-9
+                || (score.0 > best_score.0 - 1e-9 && score.1 && !best_score.1);
+            if better {
+                best = Some(idx);
+                best_score = score;
+            }
+        }
+
+        // a guess that can't split the candidates would be made forever, so guess one of them
+        let guess = match best {
+            Some(idx) if best_score.0 > 1e-9 => self.matrix.guesses()[idx],
+            _ => solutions[self.candidates[0]],
+        };
+        self.cache.insert(self.candidates.clone(), guess);
+        Some(guess)
+    }
+
+    /// Keeps only the candidates that would have given `response` to `guess`.
+    pub fn update(&mut self, guess: &str, response: &Response) {
+        let code = response.code();
+        let codes = self.codes(guess);
+        let candidates = std::mem::take(&mut self.candidates);
+        self.candidates = candidates
+            .into_iter()
+            .zip(codes)
+            .filter(|&(_, other)| other == code)
+            .map(|(idx, _)| idx)
+            .collect();
+    }
+
+    /// Plays a full game against `secret`, returning every guess made, the last one being the
+    /// secret itself. Empty if `secret` isn't one of the solutions.
+    pub fn solve(&mut self, secret: &str) -> Vec<&'static str> {
+        self.reset();
+        let mut guesses = Vec::new();
+        while let Some(guess) = self.best_guess() {
+            guesses.push(guess);
+            let response = evaluate_guess(guess, secret);
+            if response.is_solved() {
+                break;
+            }
+            self.update(guess, &response);
+        }
+        if guesses.last().is_some_and(|&guess| guess == secret) {
+            guesses
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Solves every one of the solver's solutions in turn.
+    pub fn solve_all(&mut self) -> Statistics {
+        let mut histogram = Vec::new();
+        let mut failed = 0;
+        for &secret in self.matrix.solutions().to_vec().iter() {
+            let guesses = self.solve(secret).len();
+            if guesses == 0 {
+                failed += 1;
+                continue;
+            }
+            if histogram.len() < guesses {
+                histogram.resize(guesses, 0);
+            }
+            histogram[guesses - 1] += 1;
+        }
+        Statistics { histogram, failed }
+    }
+
+    /// the response codes `guess` gets from each candidate, in order, from the matrix if it can.
+    fn codes(&self, guess: &str) -> Vec<u8> {
+        match self.matrix.guess_index(guess) {
+            Some(idx) => {
+                let row = self.matrix.row(idx);
+                self.candidates.iter().map(|&idx| row[idx]).collect()
+            }
+            None => {
+                let solutions = self.matrix.solutions();
+                self.candidates
+                    .iter()
+                    .map(|&idx| evaluate_guess(guess, solutions[idx]).code())
+                    .collect()
+            }
+        }
+    }
+
+    /// the entropy of the guess at `guess` in the guess list.
+    fn entropy_of(&self, guess: usize) -> f64 {
+        let row = self.matrix.row(guess);
+        self.split_entropy(self.candidates.iter().map(|&idx| row[idx]))
+    }
+
+    /// the entropy of how a set of response codes splits the candidates.
+    fn split_entropy(&self, codes: impl IntoIterator<Item = u8>) -> f64 {
+        let mut counts = [0usize; Response::PATTERNS];
+        for code in codes {
+            counts[usize::from(code)] += 1;
+        }
+        let total = self.candidates.len() as f64;
+        counts
+            .into_iter()
+            .filter(|&count| count > 0)
+            .map(|count| {
+                let p = count as f64 / total;
+                -p * p.log2()
+            })
+            .sum()
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Statistics {
+    pub fn games(&self) -> usize {
+        self.histogram.iter().sum()
+    }
+
+    /// The mean number of guesses per game won.
+    pub fn average(&self) -> f64 {
+        let total = self
+            .histogram
+            .iter()
+            .enumerate()
+            .map(|(idx, games)| (idx + 1) * games)
+            .sum::<usize>();
+        total as f64 / self.games() as f64
+    }
+
+    /// The most guesses any game took.
+    pub fn worst(&self) -> usize {
+        self.histogram.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Clue::{Correct as C, Incorrect as I, Misplaced as M};
+
+    #[test]
+    fn entropy_counts_distinct_responses() {
+        let solver = Solver::with_words(&["cigar", "rebut"], &["cigar", "rebut"]);
+        // either word tells the two apart, one bit of information
+        assert!((solver.entropy("cigar") - 1.0).abs() < 1e-9);
+        // four words sharing every letter but the first
+        let solver = Solver::with_words(&[], &["baker", "faker", "maker", "taker"]);
+        let split = -(0.25 * 0.25f64.log2() + 0.75 * 0.75f64.log2());
+        assert!((solver.entropy("baker") - split).abs() < 1e-9);
+        assert!((solver.entropy("fumes") - 1.5).abs() < 1e-9);
+        assert!((solver.entropy("fumbs") - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn filters_candidates() {
+        let mut solver = Solver::with_words(&SOLUTIONS[..], &SOLUTIONS[..]);
+        solver.update("crane", &Response([I, M, C, I, C]));
+        assert_ne!(solver.candidates().len(), 0);
+        for candidate in solver.candidates() {
+            assert_eq!(
+                evaluate_guess("crane", candidate),
+                Response([I, M, C, I, C])
+            );
+        }
+
+        solver.reset();
+        assert_eq!(solver.candidates().len(), SOLUTIONS.len());
+    }
+
+    #[test]
+    fn solves_every_secret() {
+        let words = &SOLUTIONS[..300];
+        let mut solver = Solver::with_words(words, words);
+        for &secret in words {
+            let guesses = solver.solve(secret);
+            assert_eq!(guesses.last(), Some(&secret));
+        }
+        assert!(solver.solve("zzzzz").is_empty());
+        assert_eq!(solver.solve_all().failed, 0);
+
+        // the same guesses every time, whether or not the cache is warm
+        let stats = solver.solve_all();
+        assert_eq!(stats.games(), 300);
+        assert_eq!(stats, Solver::with_words(words, words).solve_all());
+        assert!(stats.average() < 4.0, "{:?}", stats);
+        assert!(stats.worst() <= 6, "{:?}", stats);
+    }
+
+    #[test]
+    fn guesses_candidates_when_nothing_splits_them() {
+        // "zzzzz" gets the same response from both, so the solver has to guess one outright
+        let mut solver = Solver::with_words(&["zzzzz"], &["baker", "faker"]);
+        assert_eq!(solver.solve("baker"), ["baker"]);
+        assert_eq!(solver.solve("faker"), ["baker", "faker"]);
+
+        let stats = Solver::with_words(&[], &["baker", "faker"]).solve_all();
+        assert_eq!(stats.histogram, [1, 1]);
+        assert_eq!(stats.failed, 0);
+    }
+}
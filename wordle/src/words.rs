@@ -1,4 +1,4 @@
-pub static GUESSES: &'static [&'static str; 12972] = &[
+pub static GUESSES: &[&str; 12972] = &[
     "aahed", "aalii", "aargh", "aarti", "abaca", "abaci", "aback", "abacs", "abaft", "abaka",
     "abamp", "aband", "abase", "abash", "abask", "abate", "abaya", "abbas", "abbed", "abbes",
     "abbey", "abbot", "abcee", "abeam", "abear", "abele", "abers", "abets", "abhor", "abide",
@@ -1299,7 +1299,7 @@ pub static GUESSES: &'static [&'static str; 12972] = &[
     "zymes", "zymic",
 ];
 
-pub static SOLUTIONS: &'static [&'static str; 2315] = &[
+pub static SOLUTIONS: &[&str; 2315] = &[
     "aback", "abase", "abate", "abbey", "abbot", "abhor", "abide", "abled", "abode", "abort",
     "about", "above", "abuse", "abyss", "acorn", "acrid", "actor", "acute", "adage", "adapt",
     "adept", "admin", "admit", "adobe", "adopt", "adore", "adorn", "adult", "affix", "afire",